@@ -1,7 +1,9 @@
 #[cfg(not(feature = "bindgen"))]
 fn main() {
-    #[cfg(not(feature = "rest"))]
-    // nothing to do.
+    // features are not visible to `cfg` in build scripts, check the env instead.
+    if std::env::var_os("CARGO_FEATURE_NATIVE").is_none() {
+        return;
+    }
     println!("cargo:rustc-link-lib=taos");
     if cfg!(target_os = "windows") {
         println!("cargo:rustc-link-search=C:\\TDengine\\driver");
@@ -0,0 +1,36 @@
+use libtaos::pool::*;
+use libtaos::*;
+
+use std::env::var;
+use std::time::Duration;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+    let dsn = var("TEST_TAOS_DSN").unwrap_or_else(|_| "taos://localhost:6030/log".to_string());
+    let cfg = TaosCfg::from_dsn(&dsn)?;
+
+    // validate connections by `select server_version()` on checkout.
+    let manager = TaosManager::new(cfg).validation(ValidationStrategy::Ping);
+    let pool = r2d2::Pool::builder()
+        .max_size(4)
+        .connection_timeout(Duration::from_secs(5))
+        .build(manager)?;
+
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            let pool = pool.clone();
+            std::thread::spawn(move || -> Result<(), Error> {
+                let taos = pool.get().map_err(|_| Error::ConnectionInvalid)?;
+                let res = taos.raw_query("select server_version()")?;
+                let rows = res.fetch_fields()?;
+                println!("[{}] server version: {:?}", i, rows.rows[0][0]);
+                Ok(())
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().expect("thread panicked")?;
+    }
+    println!("{:?}", pool.state());
+    Ok(())
+}
@@ -0,0 +1,294 @@
+use crate::schemaless::{SchemalessResult, TSDB_SML_PROTOCOL_TYPE, TSDB_SML_TIMESTAMP_TYPE};
+use crate::*;
+
+use async_trait::async_trait;
+
+/// Backend of a client connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Native connection by the TDengine client library, requires feature `native`.
+    Native,
+    /// REST API connection, requires feature `rest`.
+    Rest,
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        if cfg!(feature = "native") {
+            Backend::Native
+        } else {
+            Backend::Rest
+        }
+    }
+}
+
+/// Common API of native and REST clients, so that they could be used interchangeably.
+///
+/// ```ignore
+/// async fn count(taos: &dyn TaosClient) -> Result<usize, Error> {
+///     Ok(taos.query("select * from tb1").await?.rows.len())
+/// }
+/// ```
+#[async_trait]
+pub trait TaosClient: Send + Sync {
+    /// The backend of the client.
+    fn backend(&self) -> Backend;
+
+    async fn query(&self, sql: &str) -> Result<TaosQueryData, Error>;
+
+    async fn exec(&self, sql: &str) -> Result<(), Error>;
+
+    async fn describe(&self, table: &str) -> Result<TaosDescribe, Error> {
+        let data = self
+            .query(&format!("describe {}", sql::ident(table)?))
+            .await?;
+        Ok(TaosDescribe::from(data))
+    }
+
+    async fn use_database(&self, database: &str) -> Result<(), Error> {
+        self.exec(&format!("use {}", sql::ident(database)?)).await
+    }
+
+    /// All databases.
+    async fn list_databases(&self) -> Result<Vec<DatabaseInfo>, Error> {
+        meta::parse_rows(self.query("show databases").await?)
+    }
+
+    /// Super tables of database `db`.
+    async fn list_stables(&self, db: &str) -> Result<Vec<StableInfo>, Error> {
+        meta::parse_rows(
+            self.query(&format!("show {}.stables", sql::ident(db)?))
+                .await?,
+        )
+    }
+
+    /// Tables of database `db`, only child tables of `stable` if set.
+    async fn list_tables(&self, db: &str, stable: Option<&str>) -> Result<Vec<TableInfo>, Error> {
+        let tables: Vec<TableInfo> = meta::parse_rows(
+            self.query(&format!("show {}.tables", sql::ident(db)?))
+                .await?,
+        )?;
+        Ok(match stable {
+            // names are stored in lower case.
+            Some(stable) => tables
+                .into_iter()
+                .filter(|table| {
+                    table
+                        .stable_name
+                        .as_deref()
+                        .map(|name| name.eq_ignore_ascii_case(stable))
+                        .unwrap_or(false)
+                })
+                .collect(),
+            None => tables,
+        })
+    }
+
+    /// Tag definitions and values of a child table, empty for normal tables.
+    ///
+    /// Super tables are rejected, which have tags of all child tables.
+    async fn table_tags(&self, table: &str) -> Result<Vec<TagValue>, Error> {
+        let (show, name) = match table.rsplit_once('.') {
+            Some((db, name)) => (format!("show {}.stables", sql::ident(db)?), name),
+            None => ("show stables".to_string(), table),
+        };
+        let show = format!("{} like {}", show, sql::quote(name));
+        let stables: Vec<StableInfo> = meta::parse_rows(self.query(&show).await?)?;
+        if stables
+            .iter()
+            .any(|stable| stable.name.eq_ignore_ascii_case(name))
+        {
+            return Err(TaosError {
+                code: TaosCode::TscInvalidValue,
+                err: format!("{} is a super table, not a child table", table).into(),
+            }
+            .into());
+        }
+        let tags = self.describe(table).await?.tags;
+        if tags.is_empty() {
+            return Ok(Vec::new());
+        }
+        let names = tags
+            .iter()
+            .map(|tag| sql::ident(&tag.name).map(|name| name.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let data = self
+            .query(&format!(
+                "select {} from {}",
+                names.join(","),
+                sql::ident(table)?
+            ))
+            .await?;
+        Ok(meta::parse_tags(tags, data))
+    }
+
+    /// Schemaless insert, returns affected rows.
+    ///
+    /// The native implementation runs the blocking `taos_schemaless_insert` by [spawn_blocking],
+    /// which requires a tokio runtime.
+    ///
+    /// [spawn_blocking]: https://docs.rs/tokio/1/tokio/task/fn.spawn_blocking.html
+    async fn schemaless_insert(
+        &self,
+        lines: &[&str],
+        protocol: TSDB_SML_PROTOCOL_TYPE,
+        precision: TSDB_SML_TIMESTAMP_TYPE,
+    ) -> Result<i32, Error>;
+
+    /// Schemaless insert, a failed batch is split in halves recursively to isolate the bad lines,
+    /// so that the good ones are still inserted.
+    async fn schemaless_insert_bisect(
+        &self,
+        lines: &[&str],
+        protocol: TSDB_SML_PROTOCOL_TYPE,
+        precision: TSDB_SML_TIMESTAMP_TYPE,
+    ) -> Result<SchemalessResult, Error> {
+        schemaless::bisect(lines, |lines| {
+            self.schemaless_insert(lines, protocol, precision)
+        })
+        .await
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl TaosClient for crate::client::Taos {
+    fn backend(&self) -> Backend {
+        Backend::Native
+    }
+
+    async fn query(&self, sql: &str) -> Result<TaosQueryData, Error> {
+        crate::client::Taos::query(self, sql).await
+    }
+
+    async fn exec(&self, sql: &str) -> Result<(), Error> {
+        crate::client::Taos::exec(self, sql).await
+    }
+
+    async fn describe(&self, table: &str) -> Result<TaosDescribe, Error> {
+        crate::client::Taos::describe(self, table).await
+    }
+
+    async fn use_database(&self, database: &str) -> Result<(), Error> {
+        crate::client::Taos::use_database(self, database).await
+    }
+
+    async fn schemaless_insert(
+        &self,
+        lines: &[&str],
+        protocol: TSDB_SML_PROTOCOL_TYPE,
+        precision: TSDB_SML_TIMESTAMP_TYPE,
+    ) -> Result<i32, Error> {
+        // the blocking call runs in a blocking thread with a shared handle of the connection,
+        // which is kept open until the call returns even if this future is dropped.
+        #[cfg(feature = "schemaless")]
+        {
+            let taos = self.share();
+            let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+            let task = tokio::task::spawn_blocking(move || {
+                crate::client::Taos::schemaless_insert(&taos, &lines, protocol, precision)
+            });
+            match task.await {
+                Ok(res) => Ok(res?),
+                Err(err) => std::panic::resume_unwind(err.into_panic()),
+            }
+        }
+        #[cfg(not(feature = "schemaless"))]
+        {
+            let _ = (lines, protocol, precision);
+            Err(Error::Unsupported("schemaless insert", Backend::Native))
+        }
+    }
+}
+
+#[cfg(feature = "rest")]
+#[async_trait]
+impl TaosClient for crate::rest::Taos {
+    fn backend(&self) -> Backend {
+        Backend::Rest
+    }
+
+    async fn query(&self, sql: &str) -> Result<TaosQueryData, Error> {
+        crate::rest::Taos::query(self, sql).await
+    }
+
+    async fn exec(&self, sql: &str) -> Result<(), Error> {
+        crate::rest::Taos::exec(self, sql).await
+    }
+
+    async fn describe(&self, table: &str) -> Result<TaosDescribe, Error> {
+        crate::rest::Taos::describe(self, table).await
+    }
+
+    async fn use_database(&self, database: &str) -> Result<(), Error> {
+        crate::rest::Taos::use_database(self, database).await
+    }
+
+    async fn schemaless_insert(
+        &self,
+        lines: &[&str],
+        protocol: TSDB_SML_PROTOCOL_TYPE,
+        precision: TSDB_SML_TIMESTAMP_TYPE,
+    ) -> Result<i32, Error> {
+        #[cfg(feature = "schemaless")]
+        {
+            crate::rest::Taos::schemaless_insert(self, lines, protocol, precision).await
+        }
+        #[cfg(not(feature = "schemaless"))]
+        {
+            let _ = (lines, protocol, precision);
+            Err(Error::Unsupported("schemaless insert", Backend::Rest))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test backend selection
+    fn select_backend() {
+        let cfg = TaosCfgBuilder::default()
+            .ip("localhost")
+            .user("root")
+            .pass("taosdata")
+            .db("log")
+            .port(6030u16)
+            .build()
+            .unwrap();
+        assert_eq!(cfg.backend, Backend::default());
+
+        #[cfg(feature = "rest")]
+        {
+            let cfg = TaosCfgBuilder::default()
+                .ip("localhost")
+                .user("root")
+                .pass("taosdata")
+                .db("log")
+                .port(6030u16)
+                .backend(Backend::Rest)
+                .build()
+                .unwrap();
+            let taos = cfg.connect_client().unwrap();
+            assert_eq!(taos.backend(), Backend::Rest);
+        }
+        #[cfg(not(feature = "rest"))]
+        {
+            let cfg = TaosCfgBuilder::default()
+                .ip("localhost")
+                .user("root")
+                .pass("taosdata")
+                .db("log")
+                .port(6030u16)
+                .backend(Backend::Rest)
+                .build()
+                .unwrap();
+            assert!(matches!(
+                cfg.connect_client(),
+                Err(Error::BackendUnavailable(Backend::Rest))
+            ));
+        }
+    }
+}
@@ -0,0 +1,831 @@
+//! Buffered bulk writer, items are flushed in batches by a background thread.
+//!
+//! Buffered items are flushed when the number of rows or bytes reaches the thresholds, or the
+//! oldest one has been buffered for `max_delay`. Writing waits asynchronously when the queue to the
+//! background thread is full, so that producers are slowed down to the speed of the server.
+//!
+//! ```ignore
+//! let config = BulkWriterConfigBuilder::default()
+//!     .max_rows(10000usize)
+//!     .max_delay(Duration::from_secs(1))
+//!     .on_flush(|metrics: &FlushMetrics| log::info!("{:?}", metrics))
+//!     .build()?;
+//! let writer = BulkWriter::stmt(taos, "meters", config).await?;
+//! writer
+//!     .write(Row::new(
+//!         "d1001",
+//!         vec![Field::Binary("beijing".into())],
+//!         vec![Field::Timestamp(Timestamp::now()), Field::Float(10.3)],
+//!     ))
+//!     .await?;
+//! writer.close().await?;
+//! ```
+use crate::stmt::ColumnView;
+use crate::*;
+
+use std::collections::BTreeMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::{oneshot, OwnedSemaphorePermit, Semaphore};
+
+/// Upper bound of the interval between retries.
+pub const MAX_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Callback of each flush, called in the background thread.
+pub type FlushHook = Arc<dyn Fn(&FlushMetrics) + Send + Sync>;
+
+/// Thresholds and retry policy of [BulkWriter].
+#[derive(Builder, Clone)]
+#[builder(setter(into))]
+pub struct BulkWriterConfig {
+    /// Flush when buffered rows reach the number, 10000 by default.
+    #[builder(default = "10000")]
+    max_rows: usize,
+    /// Flush when estimated size of buffered rows reaches the bytes, 4 MiB by default.
+    #[builder(default = "4 * 1024 * 1024")]
+    max_bytes: usize,
+    /// Flush when the oldest buffered row has waited for the duration, 1 second by default.
+    #[builder(default = "Duration::from_secs(1)")]
+    max_delay: Duration,
+    /// Rows queued to the background thread before writing waits, 10000 by default.
+    #[builder(default = "10000")]
+    queue_size: usize,
+    /// Retries of a flush failed with transient error, 3 by default.
+    #[builder(default = "3")]
+    max_retries: u32,
+    /// Interval before the first retry, doubled for each next one up to [MAX_RETRY_INTERVAL],
+    /// 100 ms by default.
+    #[builder(default = "Duration::from_millis(100)")]
+    retry_interval: Duration,
+    #[builder(default, setter(custom))]
+    on_flush: Option<FlushHook>,
+}
+
+impl BulkWriterConfigBuilder {
+    /// Set callback of each flush.
+    pub fn on_flush(&mut self, hook: impl Fn(&FlushMetrics) + Send + Sync + 'static) -> &mut Self {
+        self.on_flush = Some(Some(Arc::new(hook)));
+        self
+    }
+}
+
+impl Default for BulkWriterConfig {
+    fn default() -> Self {
+        BulkWriterConfigBuilder::default()
+            .build()
+            .expect("all fields have default values")
+    }
+}
+
+impl fmt::Debug for BulkWriterConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BulkWriterConfig")
+            .field("max_rows", &self.max_rows)
+            .field("max_bytes", &self.max_bytes)
+            .field("max_delay", &self.max_delay)
+            .field("queue_size", &self.queue_size)
+            .field("max_retries", &self.max_retries)
+            .field("retry_interval", &self.retry_interval)
+            .field("on_flush", &self.on_flush.is_some())
+            .finish()
+    }
+}
+
+/// Why a flush is triggered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushReason {
+    Rows,
+    Bytes,
+    Delay,
+    /// By [BulkWriter::flush].
+    Manual,
+    /// By closing the writer.
+    Close,
+}
+
+/// Metrics of a flush.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlushMetrics {
+    pub reason: FlushReason,
+    pub rows: usize,
+    /// Estimated size of the rows.
+    pub bytes: usize,
+    pub elapsed: Duration,
+    pub retries: u32,
+    /// Error of the flush, the rows are dropped.
+    pub error: Option<String>,
+}
+
+/// Accumulated metrics of a writer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BulkStats {
+    pub flushes: usize,
+    pub rows: usize,
+    pub failed_flushes: usize,
+    pub failed_rows: usize,
+    pub retries: usize,
+}
+
+/// Where [BulkWriter] inserts batches to.
+pub trait BulkSink: Send + Sync + 'static {
+    type Item: Send + 'static;
+
+    /// Estimated size of an item in bytes.
+    fn size(&self, item: &Self::Item) -> usize;
+
+    /// Check an item before it is queued.
+    fn check(&self, _item: &Self::Item) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Insert a batch, it may be called again with the same batch to retry.
+    fn insert(&self, items: &[Self::Item]) -> Result<(), TaosError>;
+}
+
+/// Errors worth retrying, the server is busy or not ready.
+fn is_transient(err: &TaosError) -> bool {
+    matches!(
+        err.code,
+        TaosCode::RpcActionInProgress
+            | TaosCode::RpcNotReady
+            | TaosCode::RpcTooSlow
+            | TaosCode::AppNotReady
+            | TaosCode::TscActionInProgress
+            | TaosCode::VndIsFlowctrl
+            | TaosCode::VndNotSynced
+    )
+}
+
+fn field_size(field: &Field) -> usize {
+    match field {
+        Field::Null => 1,
+        Field::Bool(_) | Field::TinyInt(_) | Field::UTinyInt(_) => 1,
+        Field::SmallInt(_) | Field::USmallInt(_) => 2,
+        Field::Int(_) | Field::UInt(_) | Field::Float(_) => 4,
+        Field::BigInt(_) | Field::UBigInt(_) | Field::Double(_) | Field::Timestamp(_) => 8,
+        Field::Binary(v) => v.len(),
+        Field::NChar(v) => v.len(),
+        Field::Json(v) => v.to_string().len(),
+    }
+}
+
+/// A row of a child table, which is created with the tags if not exists.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub tbname: String,
+    pub tags: Vec<Field>,
+    pub values: Vec<Field>,
+}
+
+impl Row {
+    pub fn new(tbname: impl Into<String>, tags: Vec<Field>, values: Vec<Field>) -> Self {
+        Self {
+            tbname: tbname.into(),
+            tags,
+            values,
+        }
+    }
+}
+
+/// Insert rows of child tables of a super table by [Stmt](crate::stmt::Stmt).
+///
+/// Rows are grouped by table name and bound by `set_tbname_tags`, tags of the first row of each
+/// table are used.
+pub struct StmtSink {
+    taos: Taos,
+    sql: String,
+    columns: Vec<ColumnMeta>,
+    tags: Vec<ColumnMeta>,
+    /// Timestamps are converted to the precision of the database.
+    precision: TimestampPrecision,
+}
+
+impl StmtSink {
+    /// Prepare insertion of super table `stable` by its schema.
+    pub async fn new(taos: Taos, stable: &str) -> Result<Self, Error> {
+        let describe = taos.describe(stable).await?;
+        let stable = sql::ident(stable)?;
+        let precision = taos
+            .query_a(format!("select * from {} limit 0", stable))
+            .await?
+            .precision();
+        let placeholders = |n: usize| vec!["?"; n].join(",");
+        let sql = format!(
+            "insert into ? using {} tags ({}) values ({})",
+            stable,
+            placeholders(describe.tags.len()),
+            placeholders(describe.cols.len())
+        );
+        Ok(Self {
+            taos,
+            sql,
+            columns: describe.cols,
+            tags: describe.tags,
+            precision,
+        })
+    }
+}
+
+/// Binary values are limited by bytes, and NChar values by characters.
+fn check_width(meta: &ColumnMeta, value: &Field) -> Result<(), Error> {
+    let width = match (meta.type_, value) {
+        (TaosDataType::Binary, Field::Binary(v)) => v.len(),
+        (TaosDataType::Binary, Field::NChar(v)) => v.len(),
+        (TaosDataType::NChar, Field::NChar(v)) => v.chars().count(),
+        (TaosDataType::NChar, Field::Binary(v)) => String::from_utf8_lossy(v).chars().count(),
+        _ => return Ok(()),
+    };
+    if width > meta.bytes as usize {
+        return Err(TaosError {
+            code: TaosCode::TscInvalidValue,
+            err: format!(
+                "length {} of {} exceeds {:?}({})",
+                width, meta.name, meta.type_, meta.bytes
+            )
+            .into(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+impl BulkSink for StmtSink {
+    type Item = Row;
+
+    fn size(&self, row: &Row) -> usize {
+        row.tbname.len() + row.values.iter().map(field_size).sum::<usize>()
+    }
+
+    fn check(&self, row: &Row) -> Result<(), Error> {
+        sql::ident(&row.tbname)?;
+        if row.tags.len() != self.tags.len() || row.values.len() != self.columns.len() {
+            return Err(TaosError {
+                code: TaosCode::TscInvalidValue,
+                err: format!(
+                    "expect {} tags and {} values, got {} and {} for table {}",
+                    self.tags.len(),
+                    self.columns.len(),
+                    row.tags.len(),
+                    row.values.len(),
+                    row.tbname
+                )
+                .into(),
+            }
+            .into());
+        }
+        // a bad value fails the whole batch in background, reject it early.
+        let tags = self.tags.iter().zip(&row.tags);
+        for (meta, value) in tags.chain(self.columns.iter().zip(&row.values)) {
+            match (meta.type_, value) {
+                (TaosDataType::Json, Field::Json(_) | Field::Null) => (),
+                (ty, value) => {
+                    ColumnView::from_fields(ty, self.precision, std::iter::once(value))?;
+                }
+            }
+            check_width(meta, value)?;
+        }
+        Ok(())
+    }
+
+    fn insert(&self, rows: &[Row]) -> Result<(), TaosError> {
+        let mut tables: BTreeMap<&str, (&[Field], Vec<&Row>)> = BTreeMap::new();
+        for row in rows {
+            tables
+                .entry(row.tbname.as_str())
+                .or_insert_with(|| (&row.tags, Vec::new()))
+                .1
+                .push(row);
+        }
+        let mut stmt = self.taos.stmt(self.sql.as_str())?;
+        for (tbname, (tags, rows)) in tables {
+            let tags = tags
+                .iter()
+                .map(|tag| match tag {
+                    Field::Timestamp(ts) => ts
+                        .to_precision(self.precision)
+                        .map(Field::Timestamp)
+                        .ok_or_else(|| TaosError {
+                            code: TaosCode::TscInvalidValue,
+                            err: format!(
+                                "timestamp {:?} out of {:?} precision",
+                                ts, self.precision
+                            )
+                            .into(),
+                        }),
+                    tag => Ok(tag.clone()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            stmt.set_tbname_tags(tbname, tags.iter())?;
+            let columns = self
+                .columns
+                .iter()
+                .enumerate()
+                .map(|(i, meta)| {
+                    ColumnView::from_fields(
+                        meta.type_,
+                        self.precision,
+                        rows.iter().map(|row| &row.values[i]),
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            stmt.bind_batch(&columns)?;
+        }
+        stmt.execute()
+    }
+}
+
+/// Insert lines by schemaless insert.
+#[cfg(feature = "schemaless")]
+pub struct LineSink {
+    taos: Taos,
+    protocol: schemaless::TSDB_SML_PROTOCOL_TYPE,
+    precision: schemaless::TSDB_SML_TIMESTAMP_TYPE,
+}
+
+#[cfg(feature = "schemaless")]
+impl LineSink {
+    pub fn new(
+        taos: Taos,
+        protocol: schemaless::TSDB_SML_PROTOCOL_TYPE,
+        precision: schemaless::TSDB_SML_TIMESTAMP_TYPE,
+    ) -> Self {
+        Self {
+            taos,
+            protocol,
+            precision,
+        }
+    }
+}
+
+#[cfg(feature = "schemaless")]
+impl BulkSink for LineSink {
+    type Item = String;
+
+    fn size(&self, line: &String) -> usize {
+        line.len()
+    }
+
+    fn insert(&self, lines: &[String]) -> Result<(), TaosError> {
+        self.taos
+            .schemaless_insert(lines, self.protocol, self.precision)
+            .map(|_| ())
+    }
+}
+
+enum Message<T> {
+    /// An item with its place in the queue.
+    Item(T, OwnedSemaphorePermit),
+    Flush(oneshot::Sender<Result<FlushMetrics, Error>>),
+    Close(oneshot::Sender<Result<FlushMetrics, Error>>),
+}
+
+/// Buffer of the background thread.
+/// Interval before the retry, doubled for each retry and capped by [MAX_RETRY_INTERVAL].
+fn backoff(interval: Duration, retries: u32) -> Duration {
+    let interval = 2u32
+        .checked_pow(retries)
+        .and_then(|factor| interval.checked_mul(factor));
+    interval
+        .unwrap_or(MAX_RETRY_INTERVAL)
+        .min(MAX_RETRY_INTERVAL)
+}
+
+struct Worker<S: BulkSink> {
+    sink: Arc<S>,
+    config: BulkWriterConfig,
+    stats: Arc<Mutex<BulkStats>>,
+    buffer: Vec<S::Item>,
+    bytes: usize,
+    /// When the oldest buffered item arrived.
+    since: Option<Instant>,
+}
+
+impl<S: BulkSink> Worker<S> {
+    fn run(mut self, receiver: Receiver<Message<S::Item>>) {
+        loop {
+            let message = match self.since {
+                Some(since) => {
+                    receiver.recv_timeout(self.config.max_delay.saturating_sub(since.elapsed()))
+                }
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match message {
+                Ok(Message::Item(item, permit)) => {
+                    drop(permit);
+                    self.bytes += self.sink.size(&item);
+                    self.since.get_or_insert_with(Instant::now);
+                    self.buffer.push(item);
+                    if self.buffer.len() >= self.config.max_rows {
+                        let _ = self.flush(FlushReason::Rows);
+                    } else if self.bytes >= self.config.max_bytes {
+                        let _ = self.flush(FlushReason::Bytes);
+                    }
+                }
+                Ok(Message::Flush(reply)) => {
+                    let _ = reply.send(self.flush(FlushReason::Manual));
+                }
+                Ok(Message::Close(reply)) => {
+                    let _ = reply.send(self.flush(FlushReason::Close));
+                    break;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    let _ = self.flush(FlushReason::Delay);
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    let _ = self.flush(FlushReason::Close);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Insert buffered items with retries, the items are dropped even if failed.
+    fn flush(&mut self, reason: FlushReason) -> Result<FlushMetrics, Error> {
+        let start = Instant::now();
+        let mut retries = 0;
+        let res = if self.buffer.is_empty() {
+            Ok(())
+        } else {
+            loop {
+                match self.sink.insert(&self.buffer) {
+                    Err(err) if retries < self.config.max_retries && is_transient(&err) => {
+                        warn!("bulk writer flush failed and will retry: {}", err);
+                        std::thread::sleep(backoff(self.config.retry_interval, retries));
+                        retries += 1;
+                    }
+                    res => break res,
+                }
+            }
+        };
+        let metrics = FlushMetrics {
+            reason,
+            rows: self.buffer.len(),
+            bytes: self.bytes,
+            elapsed: start.elapsed(),
+            retries,
+            error: res.as_ref().err().map(|err| err.to_string()),
+        };
+        self.buffer.clear();
+        self.bytes = 0;
+        self.since = None;
+        if metrics.rows > 0 {
+            let mut stats = self.stats.lock().unwrap();
+            stats.flushes += 1;
+            stats.rows += metrics.rows;
+            stats.retries += retries as usize;
+            if res.is_err() {
+                stats.failed_flushes += 1;
+                stats.failed_rows += metrics.rows;
+            }
+            drop(stats);
+            if let Some(hook) = &self.config.on_flush {
+                hook(&metrics);
+            }
+        }
+        res?;
+        Ok(metrics)
+    }
+}
+
+/// Buffered writer, see [module documentation](crate::bulk).
+///
+/// Items left in the buffer are flushed in background when the writer is dropped, use
+/// [BulkWriter::close] to wait for it.
+pub struct BulkWriter<S: BulkSink> {
+    sink: Arc<S>,
+    sender: Sender<Message<S::Item>>,
+    /// Places in the queue, released once the background thread receives the item.
+    permits: Arc<Semaphore>,
+    stats: Arc<Mutex<BulkStats>>,
+}
+
+impl<S: BulkSink> BulkWriter<S> {
+    /// Start the background thread writing to `sink`.
+    pub fn new(sink: S, config: BulkWriterConfig) -> Self {
+        let sink = Arc::new(sink);
+        let stats = Arc::new(Mutex::new(BulkStats::default()));
+        let permits = Arc::new(Semaphore::new(config.queue_size.max(1)));
+        let (sender, receiver) = mpsc::channel();
+        let worker = Worker {
+            sink: sink.clone(),
+            config,
+            stats: stats.clone(),
+            buffer: Vec::new(),
+            bytes: 0,
+            since: None,
+        };
+        std::thread::spawn(move || worker.run(receiver));
+        Self {
+            sink,
+            sender,
+            permits,
+            stats,
+        }
+    }
+
+    fn send(&self, message: Message<S::Item>) -> Result<(), Error> {
+        self.sender.send(message).map_err(|_| Error::WriterClosed)
+    }
+
+    /// Queue an item, waits if the queue is full.
+    pub async fn write(&self, item: S::Item) -> Result<(), Error> {
+        self.sink.check(&item)?;
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| Error::WriterClosed)?;
+        self.send(Message::Item(item, permit))
+    }
+
+    /// Flush items queued before, returns error if the flush failed.
+    pub async fn flush(&self) -> Result<FlushMetrics, Error> {
+        let (reply, result) = oneshot::channel();
+        self.send(Message::Flush(reply))?;
+        result.await.map_err(|_| Error::WriterClosed)?
+    }
+
+    pub fn stats(&self) -> BulkStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Flush the rest items and stop the background thread.
+    pub async fn close(self) -> Result<FlushMetrics, Error> {
+        let (reply, result) = oneshot::channel();
+        self.send(Message::Close(reply))?;
+        result.await.map_err(|_| Error::WriterClosed)?
+    }
+}
+
+impl BulkWriter<StmtSink> {
+    /// Writer of rows of child tables of super table `stable`, see [StmtSink].
+    pub async fn stmt(taos: Taos, stable: &str, config: BulkWriterConfig) -> Result<Self, Error> {
+        Ok(Self::new(StmtSink::new(taos, stable).await?, config))
+    }
+}
+
+#[cfg(feature = "schemaless")]
+impl BulkWriter<LineSink> {
+    /// Writer of schemaless lines, see [LineSink].
+    pub fn lines(
+        taos: Taos,
+        protocol: schemaless::TSDB_SML_PROTOCOL_TYPE,
+        precision: schemaless::TSDB_SML_TIMESTAMP_TYPE,
+        config: BulkWriterConfig,
+    ) -> Self {
+        Self::new(LineSink::new(taos, protocol, precision), config)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Record sizes of batches, fail the first `failures` attempts with `code`.
+    struct FakeSink {
+        batches: Mutex<Vec<usize>>,
+        failures: Mutex<usize>,
+        code: TaosCode,
+    }
+
+    impl FakeSink {
+        fn new(failures: usize, code: TaosCode) -> Self {
+            Self {
+                batches: Mutex::new(Vec::new()),
+                failures: Mutex::new(failures),
+                code,
+            }
+        }
+    }
+
+    impl BulkSink for FakeSink {
+        type Item = String;
+
+        fn size(&self, item: &String) -> usize {
+            item.len()
+        }
+
+        fn insert(&self, items: &[String]) -> Result<(), TaosError> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(TaosError {
+                    code: self.code,
+                    err: "fake error".into(),
+                });
+            }
+            self.batches.lock().unwrap().push(items.len());
+            Ok(())
+        }
+    }
+
+    fn builder() -> BulkWriterConfigBuilder {
+        let mut builder = BulkWriterConfigBuilder::default();
+        builder
+            .max_delay(Duration::from_secs(60))
+            .retry_interval(Duration::from_millis(1));
+        builder
+    }
+
+    #[tokio::test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test bulk writer flushing by thresholds
+    async fn bulk_flush_thresholds() -> Result<(), Error> {
+        let reasons = Arc::new(Mutex::new(Vec::new()));
+        let hook = reasons.clone();
+        let config = builder()
+            .max_rows(3usize)
+            .max_bytes(10usize)
+            .on_flush(move |metrics: &FlushMetrics| hook.lock().unwrap().push(metrics.reason))
+            .build()
+            .unwrap();
+        let writer = BulkWriter::new(FakeSink::new(0, TaosCode::Success), config);
+        for _ in 0..4 {
+            writer.write("a".to_string()).await?;
+        }
+        writer.write("0123456789".to_string()).await?;
+        let metrics = writer.flush().await?;
+        assert_eq!(metrics.reason, FlushReason::Manual);
+        assert_eq!(metrics.rows, 0);
+        writer.write("a".to_string()).await?;
+        let sink = writer.sink.clone();
+        assert_eq!(writer.close().await?.rows, 1);
+        assert_eq!(*sink.batches.lock().unwrap(), vec![3, 2, 1]);
+        assert_eq!(
+            *reasons.lock().unwrap(),
+            vec![FlushReason::Rows, FlushReason::Bytes, FlushReason::Close]
+        );
+
+        let config = builder()
+            .max_delay(Duration::from_millis(10))
+            .build()
+            .unwrap();
+        let writer = BulkWriter::new(FakeSink::new(0, TaosCode::Success), config);
+        writer.write("a".to_string()).await?;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(writer.stats().flushes, 1);
+        assert_eq!(*writer.sink.batches.lock().unwrap(), vec![1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test bulk writer retrying transient errors
+    async fn bulk_flush_retry() -> Result<(), Error> {
+        let writer = BulkWriter::new(
+            FakeSink::new(2, TaosCode::RpcNotReady),
+            builder().build().unwrap(),
+        );
+        writer.write("a".to_string()).await?;
+        let metrics = writer.flush().await?;
+        assert_eq!(metrics.retries, 2);
+        assert_eq!(metrics.error, None);
+
+        let writer = BulkWriter::new(
+            FakeSink::new(1, TaosCode::TscInvalidValue),
+            builder().build().unwrap(),
+        );
+        writer.write("a".to_string()).await?;
+        writer.write("b".to_string()).await?;
+        assert!(matches!(writer.flush().await, Err(Error::RawTaosError(_))));
+        let stats = writer.stats();
+        assert_eq!(stats.failed_flushes, 1);
+        assert_eq!(stats.failed_rows, 2);
+        assert_eq!(stats.retries, 0);
+
+        let interval = Duration::from_millis(100);
+        assert_eq!(backoff(interval, 0), interval);
+        assert_eq!(backoff(interval, 3), interval * 8);
+        assert_eq!(backoff(interval, 40), MAX_RETRY_INTERVAL);
+        assert_eq!(backoff(Duration::MAX, 1), MAX_RETRY_INTERVAL);
+        Ok(())
+    }
+
+    /// Block each insert until released.
+    struct GateSink(Mutex<mpsc::Receiver<()>>);
+
+    impl BulkSink for GateSink {
+        type Item = String;
+
+        fn size(&self, item: &String) -> usize {
+            item.len()
+        }
+
+        fn insert(&self, _items: &[String]) -> Result<(), TaosError> {
+            let _ = self.0.lock().unwrap().recv();
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test bulk writer waits when the queue is full
+    async fn bulk_backpressure() -> Result<(), Error> {
+        let (release, gate) = mpsc::channel();
+        let config = builder()
+            .max_rows(1usize)
+            .queue_size(1usize)
+            .build()
+            .unwrap();
+        let writer = BulkWriter::new(GateSink(Mutex::new(gate)), config);
+        // the first one is flushing, and the second one is queued.
+        writer.write("a".to_string()).await?;
+        writer.write("b".to_string()).await?;
+        let timeout = Duration::from_millis(100);
+        assert!(tokio::time::timeout(timeout, writer.write("c".to_string()))
+            .await
+            .is_err());
+        release.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), writer.write("c".to_string()))
+            .await
+            .expect("write after the queue is drained")?;
+        drop(release);
+        writer.close().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test bulk writer with stmt
+    async fn bulk_stmt() -> Result<(), Error> {
+        let taos = crate::test::taos()?;
+        let db = "rs_bulk_stmt";
+        taos.exec(format!("drop database if exists {}", db)).await?;
+        taos.exec(format!("create database if not exists {} keep 36500", db))
+            .await?;
+        taos.exec(format!("use {}", db)).await?;
+        taos.exec(
+            "create stable st (ts timestamp, v int, note binary(16)) tags (location binary(16))",
+        )
+        .await?;
+
+        let writer = BulkWriter::stmt(
+            crate::test::taos()?,
+            &format!("{}.st", db),
+            builder().max_rows(100usize).build().unwrap(),
+        )
+        .await?;
+        for i in 0..250 {
+            writer
+                .write(Row::new(
+                    format!("{}.tb{}", db, i % 3),
+                    vec![Field::Binary(format!("loc{}", i % 3).into())],
+                    vec![
+                        Field::Timestamp(Timestamp::new(
+                            1626006833639 + i,
+                            TimestampPrecision::Milli,
+                        )),
+                        Field::Int(i as _),
+                        if i % 2 == 0 {
+                            Field::Binary("even".into())
+                        } else {
+                            Field::Null
+                        },
+                    ],
+                ))
+                .await?;
+        }
+        assert!(writer
+            .write(Row::new("tb0", vec![], vec![Field::Int(1)]))
+            .await
+            .is_err());
+        let row = Row::new(
+            "tb0",
+            vec![Field::Binary("loc0".into())],
+            vec![
+                Field::Timestamp(Timestamp::now()),
+                Field::BigInt(1),
+                Field::Null,
+            ],
+        );
+        assert!(matches!(
+            writer.write(row).await,
+            Err(Error::RawTaosError(_))
+        ));
+        let row = Row::new(
+            "tb0",
+            vec![Field::Binary("loc0".into())],
+            vec![
+                Field::Timestamp(Timestamp::now()),
+                Field::Int(1),
+                Field::Binary("a note longer than 16".into()),
+            ],
+        );
+        assert!(matches!(
+            writer.write(row).await,
+            Err(Error::RawTaosError(_))
+        ));
+        writer.close().await?;
+
+        let res = taos.query("select count(*) from st").await?;
+        assert_eq!(res.rows[0][0], Field::BigInt(250));
+        let res = taos.query("select count(tbname) from st").await?;
+        assert_eq!(res.rows.len(), 1);
+
+        taos.exec(format!("drop database {}", db)).await?;
+        Ok(())
+    }
+}
@@ -2,25 +2,56 @@ use crate::bindings::*;
 use crate::*;
 
 use std::ffi::CStr;
+use std::future::Future;
+use std::mem::ManuallyDrop;
 use std::os::raw::c_char;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::TryStreamExt;
 
 use crate::error::*;
 use crate::field::*;
 
-#[cfg(feature = "cleanup")]
-lazy_static::lazy_static! {
-    static ref TAOS_INIT_LOCK: std::sync::Mutex<u32> = std::sync::Mutex::new(0);
-}
+mod block;
+pub use block::*;
+mod cancel;
+pub use cancel::Canceller;
+mod future;
+pub use future::QueryFuture;
+mod rows;
+pub use rows::*;
+mod runtime;
+pub use runtime::TaosRuntime;
 
 #[derive(Debug)]
 pub struct Taos {
+    conn: Arc<RawConnection>,
+    broken: BrokenFlag,
+}
+
+/// The raw connection, closed once the [Taos] and blocking calls in progress are all dropped.
+#[derive(Debug)]
+struct RawConnection {
     conn: *mut TAOS,
+    _runtime: Arc<TaosRuntime>,
 }
 
-unsafe impl Send for Taos {}
-unsafe impl Sync for Taos {}
+unsafe impl Send for RawConnection {}
+unsafe impl Sync for RawConnection {}
+
+impl Drop for RawConnection {
+    fn drop(&mut self) {
+        // the runtime reference is released after closed.
+        unsafe {
+            taos_close(self.conn);
+        }
+    }
+}
 
 impl Taos {
+    /// Connect with the alive [TaosRuntime], or initialize a new one.
     pub fn new(
         ip: impl ToCString,
         user: impl ToCString,
@@ -28,46 +59,18 @@ impl Taos {
         db: impl ToCString,
         port: u16,
     ) -> Result<Self, Error> {
-        let ip = ip.to_c_string();
-        let user = user.to_c_string();
-        let pass = pass.to_c_string();
-        let db = db.to_c_string();
-
-        #[cfg(feature = "cleanup")]
-        // Call taos_init at first connection.
-        {
-            let mut n = TAOS_INIT_LOCK.lock().unwrap();
-            if *n == 0 {
-                unsafe { taos_init() };
-            }
-            *n += 1;
-        }
-        // unsafe {
-        //     taos_options(
-        //         TSDB_OPTION_TSDB_OPTION_CHARSET,
-        //         "UTF-8".to_c_string().as_ptr() as _,
-        //     );
-        // }
-        unsafe {
-            let conn = taos_connect(
-                ip.as_ptr(),
-                user.as_ptr(),
-                pass.as_ptr(),
-                db.as_ptr(),
-                port as u16,
-            )
-            .as_mut();
-            match conn {
-                None => Err(Error::ConnectionInvalid),
-                Some(conn) => Ok(Taos { conn: conn as _ }),
-            }
-        }
+        TaosRuntime::init()?.connect(ip, user, pass, db, port)
     }
 
+    /// Create table, the name is quoted by [sql::ident], `options` is raw SQL such as column definitions.
     pub async fn create_table(&self, table: &str, options: Option<&str>) -> Result<(), Error> {
-        self.query(&format!("create table {} {}", table, options.unwrap_or("")))
-            .await
-            .map(|_| ())
+        self.query(&format!(
+            "create table {} {}",
+            sql::ident(table)?,
+            options.unwrap_or("")
+        ))
+        .await
+        .map(|_| ())
     }
 
     pub async fn create_table_if_not_exists(
@@ -77,7 +80,7 @@ impl Taos {
     ) -> Result<(), Error> {
         self.query(&format!(
             "create table if not exists {} {}",
-            table,
+            sql::ident(table)?,
             options.unwrap_or("")
         ))
         .await
@@ -85,38 +88,148 @@ impl Taos {
     }
 
     pub async fn create_database(&self, database: &str) -> Result<(), Error> {
-        self.query(&format!("create database {}", database))
+        self.query(&format!("create database {}", sql::ident(database)?))
             .await
             .map(|_| ())
     }
 
     pub async fn create_database_if_exists(&self, database: &str) -> Result<(), Error> {
-        self.query(&format!("create database if not exists {}", database))
-            .await
-            .map(|_| ())
+        self.query(&format!(
+            "create database if not exists {}",
+            sql::ident(database)?
+        ))
+        .await
+        .map(|_| ())
     }
 
     pub async fn use_database(&self, database: &str) -> Result<(), Error> {
-        self.query(&format!("use {}", database)).await.map(|_| ())
+        self.query(&format!("use {}", sql::ident(database)?))
+            .await
+            .map(|_| ())
     }
 
     pub async fn describe(&self, table: &str) -> Result<TaosDescribe, Error> {
-        self.query(&format!("describe {}", table))
+        self.query(&format!("describe {}", sql::ident(table)?))
             .await
             .map(TaosDescribe::from)
     }
 
     pub async fn exec(&self, sql: impl ToCString) -> Result<(), Error> {
-        self.raw_query(sql).map(|_| ())
+        self.query_a(sql).await?;
+        Ok(())
+    }
+
+    /// Execute with timeout, the query will be stopped once timeout.
+    ///
+    /// Timer of [tokio] is used, so it should be called in a tokio runtime.
+    pub async fn exec_timeout(&self, sql: impl ToCString, timeout: Duration) -> Result<(), Error> {
+        tokio::time::timeout(timeout, self.exec(sql))
+            .await
+            .map_err(|_| Error::Timeout(timeout))?
     }
+
+    /// Query synchronously by `taos_query`, which blocks current thread.
     pub fn raw_query(&self, s: impl ToCString) -> Result<CTaosResult, Error> {
         let cstr = s.to_c_string();
-        let res = CTaosResult::new(unsafe { taos_query(self.conn, cstr.as_ptr()) })?;
+        let res = CTaosResult::new(unsafe { taos_query(self.as_raw(), cstr.as_ptr()) })
+            .map_err(|err| self.check_error(err))?
+            .watch(&self.broken);
         Ok(res)
     }
+
+    /// Query asynchronously by `taos_query_a`, the future resolves when the result set is ready.
+    pub fn query_a(&self, s: impl ToCString) -> QueryFuture<'_> {
+        QueryFuture::new(self, s, Canceller::new())
+    }
+
     pub async fn query(&self, s: &str) -> Result<TaosQueryData, Error> {
-        let res = self.raw_query(s)?;
-        Ok(res.fetch_fields())
+        self.query_with(s, Canceller::new()).await
+    }
+
+    /// Query with a handle to cancel it, cancelled query returns error [TaosCode::TscQueryCancelled].
+    ///
+    /// ```ignore
+    /// let (canceller, query) = taos.query_with_canceller("select * from m1");
+    /// tokio::spawn(async move {
+    ///     tokio::time::sleep(Duration::from_secs(1)).await;
+    ///     canceller.cancel();
+    /// });
+    /// let data = query.await?;
+    /// ```
+    pub fn query_with_canceller(
+        &self,
+        s: impl ToCString,
+    ) -> (
+        Canceller,
+        impl Future<Output = Result<TaosQueryData, Error>> + '_,
+    ) {
+        let canceller = Canceller::new();
+        let sql = s.to_c_string();
+        (canceller.clone(), self.query_with(sql, canceller))
+    }
+
+    /// Query with timeout, the query will be stopped once timeout.
+    ///
+    /// Timer of [tokio] is used, so it should be called in a tokio runtime.
+    pub async fn query_timeout(&self, s: &str, timeout: Duration) -> Result<TaosQueryData, Error> {
+        tokio::time::timeout(timeout, self.query(s))
+            .await
+            .map_err(|_| Error::Timeout(timeout))?
+    }
+
+    async fn query_with(
+        &self,
+        s: impl ToCString,
+        canceller: Canceller,
+    ) -> Result<TaosQueryData, Error> {
+        let res = QueryFuture::new(self, s, canceller.clone()).await?;
+        let rows = TaosRows::with_canceller(res, canceller);
+        let column_meta = rows.column_meta().to_vec();
+        let rows = TryStreamExt::try_collect(rows).await?;
+        Ok(TaosQueryData { column_meta, rows })
+    }
+
+    /// Query and iterate over rows lazily, rows are fetched while iterating.
+    ///
+    /// ```ignore
+    /// use futures::TryStreamExt;
+    /// let mut rows = taos.query_rows("select * from m1").await?;
+    /// while let Some(row) = rows.try_next().await? {
+    ///     println!("{}", row.into_iter().join(","));
+    /// }
+    /// ```
+    pub async fn query_rows(&self, s: impl ToCString) -> Result<TaosRows, Error> {
+        Ok(self.query_a(s).await?.rows())
+    }
+
+    /// Query and deserialize each row into `T`, see [crate::de] for details.
+    pub async fn query_as<T: serde::de::DeserializeOwned>(&self, s: &str) -> Result<Vec<T>, Error> {
+        let data = self.query(s).await?;
+        let rows = data.deserialize().collect::<Result<_, _>>()?;
+        Ok(rows)
+    }
+
+    /// Validate SQL without executing it.
+    ///
+    /// `taos_validate_sql` only returns an error code, so the message of the error is built from
+    /// the code, such as `TscSqlSyntaxError`, without details of the parser.
+    ///
+    /// ```ignore
+    /// if let Err(err) = taos.validate("select * form tb1") {
+    ///     println!("invalid sql: {}", err);
+    /// }
+    /// ```
+    pub fn validate(&self, sql: impl ToCString) -> Result<(), TaosError> {
+        let sql = sql.to_c_string();
+        let code = unsafe { taos_validate_sql(self.as_raw(), sql.as_ptr()) };
+        let code: TaosCode = (code & 0x0000ffff).into();
+        if code.success() {
+            return Ok(());
+        }
+        Err(self.check_error(TaosError {
+            code,
+            err: format!("invalid sql: {:?}", code).into(),
+        }))
     }
 
     /// Warmup table metadata cache with a list of table name, separated by comma.
@@ -128,7 +241,7 @@ impl Taos {
     ///
     pub fn load_table_info(&self, cstr: impl AsRef<CStr>) -> Result<(), Error> {
         unsafe {
-            let code = taos_load_table_info(self.conn, cstr.as_ref().as_ptr());
+            let code = taos_load_table_info(self.as_raw(), cstr.as_ref().as_ptr());
             let code: TaosCode = (code & 0x0000ffff).into();
             if !code.success() {
                 Err(TaosError {
@@ -143,32 +256,71 @@ impl Taos {
     }
 
     pub fn as_raw(&self) -> *mut TAOS {
-        self.conn
+        self.conn.conn
     }
-}
 
-impl Drop for Taos {
-    fn drop(&mut self) {
-        // reduce connection count and call clean_up after the last connection closed.
-        unsafe {
-            taos_close(self.conn);
+    pub(crate) fn from_raw(conn: *mut TAOS, runtime: Arc<TaosRuntime>) -> Self {
+        Taos {
+            conn: Arc::new(RawConnection {
+                conn,
+                _runtime: runtime,
+            }),
+            broken: BrokenFlag::default(),
         }
-        #[cfg(feature = "cleanup")]
-        {
-            let mut n = TAOS_INIT_LOCK.lock().unwrap();
-            *n -= 1;
-            if *n == 0 {
-                unsafe { taos_cleanup() };
-            }
+    }
+
+    /// Another handle of the same connection, to be moved into blocking tasks.
+    pub(crate) fn share(&self) -> Taos {
+        Taos {
+            conn: self.conn.clone(),
+            broken: self.broken.clone(),
+        }
+    }
+
+    /// Whether a fatal error, such as network unavailable or disconnected, has been seen on
+    /// the connection.
+    pub fn is_broken(&self) -> bool {
+        self.broken.get()
+    }
+
+    /// Mark the connection as broken if the error is fatal.
+    pub(crate) fn check_error(&self, err: TaosError) -> TaosError {
+        self.broken.check(err)
+    }
+
+    /// Flag shared with statements and result sets of the connection.
+    pub(crate) fn broken_flag(&self) -> &BrokenFlag {
+        &self.broken
+    }
+}
+
+/// Set once a fatal error is seen on a connection, shared with the result sets and statements
+/// of it, so errors while fetching or inserting also mark the connection.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BrokenFlag(Arc<AtomicBool>);
+
+impl BrokenFlag {
+    pub(crate) fn get(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Mark as broken if the error is fatal.
+    pub(crate) fn check(&self, err: TaosError) -> TaosError {
+        if err.is_fatal() {
+            self.0.store(true, Ordering::Relaxed);
         }
+        err
     }
 }
 
 #[derive(Debug)]
 pub struct CTaosResult {
     res: *mut bindings::TAOS_RES,
+    broken: BrokenFlag,
 }
 
+unsafe impl Send for CTaosResult {}
+
 impl CTaosResult {
     pub fn as_raw_mut_ptr(&mut self) -> *mut bindings::TAOS_RES {
         self.res
@@ -187,8 +339,24 @@ impl CTaosResult {
         }
     }
 
+    /// Give up the ownership of the result set, it will not be freed on drop.
+    pub(crate) fn release(&mut self) -> *mut TAOS_RES {
+        std::mem::replace(&mut self.res, std::ptr::null_mut())
+    }
+
+    /// Wrap a result set owned by the C library, which must not be freed.
+    pub(crate) fn borrowed(res: *mut TAOS_RES) -> ManuallyDrop<Self> {
+        ManuallyDrop::new(Self {
+            res,
+            broken: BrokenFlag::default(),
+        })
+    }
+
     pub fn new(res: *mut TAOS_RES) -> Result<Self, TaosError> {
-        let res = Self { res };
+        let res = Self {
+            res,
+            broken: BrokenFlag::default(),
+        };
         let code = res.error_code();
 
         if !code.success() {
@@ -202,19 +370,33 @@ impl CTaosResult {
         }
     }
 
+    /// Mark the connection of the result set as broken on fatal fetch errors.
+    pub(crate) fn watch(mut self, broken: &BrokenFlag) -> Self {
+        self.broken = broken.clone();
+        self
+    }
+
+    /// Mark the connection as broken if the error is fatal.
+    pub(crate) fn check_error(&self, err: TaosError) -> TaosError {
+        self.broken.check(err)
+    }
+
     pub fn affected_rows(&self) -> i32 {
         unsafe {
             taos_affected_rows(self.res)
         }
     }
 
-    pub fn fetch_fields(&self) -> TaosQueryData {
-        let fields = unsafe { taos_fetch_fields(self.res) };
+    /// Timestamp precision of the result set.
+    pub fn precision(&self) -> TimestampPrecision {
+        unsafe { taos_result_precision(self.res) }.into()
+    }
 
+    /// Column metadata of the result set.
+    pub fn column_meta(&self) -> Vec<ColumnMeta> {
+        let fields = unsafe { taos_fetch_fields(self.res) };
         let fcount = unsafe { taos_field_count(self.res) };
-        let mut rows = Vec::new();
-        let fields = (0..fcount)
-            .into_iter()
+        (0..fcount)
             .map(|i| {
                 let field = &unsafe { *fields.offset(i as _) };
                 let name = unsafe { CStr::from_ptr(&field.name as _) }
@@ -226,71 +408,81 @@ impl CTaosResult {
                     bytes: field.bytes,
                 }
             })
-            .collect_vec();
+            .collect_vec()
+    }
 
-        while let Some(taos_row) = unsafe { taos_fetch_row(self.res).as_ref() } {
-            let lengths =
-                unsafe { std::slice::from_raw_parts(taos_fetch_lengths(self.res), fcount as _) };
-            let row = unsafe { std::slice::from_raw_parts(taos_row, fcount as usize) }
-                .iter()
-                .zip(fields.iter())
-                .zip(lengths.iter())
-                .map(|((ptr, meta), length)| unsafe {
-                    if ptr.is_null() {
-                        return Field::Null;
-                    }
-                    //let ptr = taos_row.offset(i as _);
-                    match meta.type_ {
-                        TaosDataType::Null => Field::Null,
-                        TaosDataType::Bool => Field::Bool(*(*ptr as *mut i8) != 0),
-                        TaosDataType::TinyInt => Field::TinyInt(*(*ptr as *mut i8)),
-                        TaosDataType::SmallInt => Field::SmallInt(*(*ptr as *mut i16)),
-                        TaosDataType::Int => Field::Int(*(*ptr as *mut i32)),
-                        TaosDataType::BigInt => Field::BigInt(*(*ptr as *mut i64)),
-                        TaosDataType::UTinyInt => Field::UTinyInt(*(*ptr as *mut u8)),
-                        TaosDataType::USmallInt => Field::USmallInt(*(*ptr as *mut u16)),
-                        TaosDataType::UInt => Field::UInt(*(*ptr as *mut u32)),
-                        TaosDataType::UBigInt => Field::UBigInt(*(*ptr as *mut u64)),
-                        TaosDataType::Timestamp => Field::Timestamp(Timestamp::new(
-                            *(*ptr as *mut i64),
-                            taos_result_precision(self.res),
-                        )),
-                        TaosDataType::Float => Field::Float(*(*ptr as *mut f32)),
-                        TaosDataType::Double => Field::Double(*(*ptr as *mut f64)),
-                        TaosDataType::Binary => Field::Binary({
-                            std::slice::from_raw_parts((*ptr) as *mut u8, *length as _).into()
-                        }),
-                        TaosDataType::NChar => {
-                            let slice = std::slice::from_raw_parts((*ptr) as *mut u8, *length as _);
-                            let s = String::from_utf8_lossy(slice).to_string();
-                            Field::NChar(s)
-                        }
-                        TaosDataType::Json => {
-                            let slice = std::slice::from_raw_parts((*ptr) as *mut u8, *length as _);
-                            serde_json::from_slice(slice)
-                                .ok()
-                                .map(Field::Json)
-                                .unwrap_or(Field::Null)
-                        }
-                        _ => {
-                            unreachable!("unexpected data type, please contact the author to fix!")
-                        }
-                    }
-                })
-                .collect_vec();
-            // std::mem::forget(lengths);
+    /// Fetch the next row, `None` means all rows have been fetched.
+    pub(crate) fn fetch_row(
+        &self,
+        fields: &[ColumnMeta],
+        precision: TimestampPrecision,
+    ) -> Result<Option<Vec<Field>>, TaosError> {
+        let taos_row = unsafe { taos_fetch_row(self.res) };
+        if taos_row.is_null() {
+            let code = self.error_code();
+            if !code.success() {
+                return Err(self.check_error(TaosError {
+                    code,
+                    err: Cow::from(self.error_string()),
+                }));
+            }
+            return Ok(None);
+        }
+        let row = unsafe { self.to_row(taos_row, fields, precision) };
+        Ok(Some(row))
+    }
+
+    /// Convert current row of the result set to fields.
+    pub(crate) unsafe fn to_row(
+        &self,
+        row: TAOS_ROW,
+        fields: &[ColumnMeta],
+        precision: TimestampPrecision,
+    ) -> Vec<Field> {
+        let lengths = std::slice::from_raw_parts(taos_fetch_lengths(self.res), fields.len());
+        std::slice::from_raw_parts(row, fields.len())
+            .iter()
+            .zip(fields.iter())
+            .zip(lengths.iter())
+            .map(|((ptr, meta), length)| {
+                if ptr.is_null() {
+                    return Field::Null;
+                }
+                to_field(*ptr, meta.type_, *length as _, precision)
+            })
+            .collect_vec()
+    }
+
+    /// Fetch all the rows, stops at the first error of fetching.
+    pub fn fetch_fields(&self) -> Result<TaosQueryData, TaosError> {
+        let fields = self.column_meta();
+        let precision = self.precision();
+        let mut rows = Vec::new();
+        while let Some(row) = self.fetch_row(&fields, precision)? {
             rows.push(row);
         }
-        TaosQueryData {
+        Ok(TaosQueryData {
             column_meta: fields,
             rows,
-        }
+        })
+    }
+
+    /// Consume the result set into a lazy row iterator.
+    pub fn rows(self) -> TaosRows {
+        TaosRows::new(self)
+    }
+
+    /// Consume the result set into an iterator of row blocks.
+    pub fn blocks(self) -> TaosBlocks {
+        TaosBlocks::new(self)
     }
 }
 impl Drop for CTaosResult {
     fn drop(&mut self) {
-        unsafe {
-            taos_free_result(self.res);
+        if !self.res.is_null() {
+            unsafe {
+                taos_free_result(self.res);
+            }
         }
     }
 }
@@ -299,8 +491,28 @@ impl Drop for CTaosResult {
 mod test {
     use crate::test::taos;
     use crate::*;
+    use itertools::Itertools;
     use proc_test_catalog::test_catalogue;
 
+    #[test]
+    #[test_catalogue]
+    /// Test fatal errors of result sets and statements mark the connection as broken
+    fn broken_flag_shared() {
+        let broken = BrokenFlag::default();
+        let res = broken.clone();
+        let err = res.check(TaosError {
+            code: TaosCode::TscInvalidValue,
+            err: "invalid value".into(),
+        });
+        assert!(!err.is_fatal());
+        assert!(!broken.get());
+        res.check(TaosError {
+            code: TaosCode::RpcNetworkUnavail,
+            err: "Unable to establish connection".into(),
+        });
+        assert!(broken.get());
+    }
+
     #[tokio::test]
     #[test_catalogue]
     /// Test TS-781 Bug for binary
@@ -346,6 +558,120 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    #[test_catalogue]
+    /// Test lazy rows and blocks fetching
+    async fn query_rows() -> Result<(), Error> {
+        let taos = taos()?;
+        let db = "rs_query_rows";
+        taos.exec(format!("drop database if exists {}", db)).await?;
+        taos.exec(format!("create database if not exists {} keep 36500", db))
+            .await?;
+        taos.exec(format!("use {}", db)).await?;
+        taos.exec("create table tb1 (ts timestamp, v int, name binary(10), n nchar(10))")
+            .await?;
+        taos.exec("insert into tb1 values(1626006833639, 1, 'abc', '涛思') (1626006833640, NULL, NULL, NULL)")
+            .await?;
+
+        let rows = taos.query_rows("select * from tb1").await?;
+        assert_eq!(rows.column_meta().len(), 4);
+        let rows = rows.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][1], Field::Int(1));
+        assert_eq!(rows[1][1], Field::Null);
+
+        let blocks = taos.raw_query("select * from tb1")?.blocks();
+        let block_rows = blocks.flatten_ok().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(rows, block_rows);
+
+        let mut blocks = taos.raw_query("select * from tb1")?.blocks();
+        let block = blocks.fetch_block()?.expect("one block");
+        assert_eq!(block.nrows(), 2);
+        match block.column(0) {
+            Column::Timestamp(ts) => assert_eq!(&*ts, &[1626006833639, 1626006833640]),
+            column => panic!("unexpected column {:?}", column),
+        }
+        assert_eq!(block.nulls(1).null_count(), 1);
+        match block.column(2) {
+            Column::Binary(names) => assert_eq!(names.get(0), b"abc"),
+            column => panic!("unexpected column {:?}", column),
+        }
+        assert!(blocks.fetch_block()?.is_none());
+
+        taos.exec(format!("drop database {}", db)).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_catalogue]
+    /// Test concurrent async queries
+    async fn query_async() -> Result<(), Error> {
+        use futures::TryStreamExt;
+        let taos = std::sync::Arc::new(taos()?);
+        let db = "rs_query_async";
+        taos.exec(format!("drop database if exists {}", db)).await?;
+        taos.exec(format!("create database if not exists {} keep 36500", db))
+            .await?;
+        taos.exec(format!("use {}", db)).await?;
+        taos.exec("create table tb1 (ts timestamp, v int)").await?;
+        let values = (0..1000)
+            .map(|i| format!("({}, {})", 1626006833639i64 + i, i))
+            .join(" ");
+        taos.exec(format!("insert into tb1 values {}", values))
+            .await?;
+
+        // spawned tasks require the futures to be Send.
+        let tasks = (0..100)
+            .map(|_| {
+                let taos = taos.clone();
+                tokio::spawn(async move {
+                    let rows = taos.query_rows("select * from tb1").await?;
+                    let rows: Vec<_> = TryStreamExt::try_collect(rows).await?;
+                    Ok::<_, Error>(rows.len())
+                })
+            })
+            .collect_vec();
+        for task in futures::future::join_all(tasks).await {
+            assert_eq!(task.unwrap()?, 1000);
+        }
+
+        let err = taos
+            .query_a("select * from not_exist_table")
+            .await
+            .unwrap_err();
+        assert!(!err.code.success());
+
+        taos.exec(format!("drop database {}", db)).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_catalogue]
+    /// Test sql validation without execution
+    async fn validate_sql() -> Result<(), Error> {
+        let taos = taos()?;
+        let db = "rs_validate_sql";
+        taos.exec(format!("drop database if exists {}", db)).await?;
+        taos.exec(format!("create database if not exists {} keep 36500", db))
+            .await?;
+        taos.exec(format!("use {}", db)).await?;
+        taos.exec("create table tb1 (ts timestamp, v int)").await?;
+
+        taos.validate("select avg(v) from tb1 interval(1m)")?;
+        taos.validate("insert into tb1 values(now, 1)")?;
+        // validated only, not inserted.
+        assert!(taos.query("select * from tb1").await?.rows.is_empty());
+
+        let err = taos.validate("select * form tb1").unwrap_err();
+        assert_eq!(err.code, TaosCode::TscSqlSyntaxError);
+        assert!(!err.err.is_empty());
+        let err = taos.validate("select * from not_exist_table").unwrap_err();
+        assert!(!err.code.success());
+
+        taos.exec(format!("drop database {}", db)).await?;
+        Ok(())
+    }
+
     #[tokio::test]
     #[test_catalogue]
     /// Test json tag format
@@ -0,0 +1,357 @@
+use crate::bindings::*;
+use crate::*;
+
+use std::borrow::Cow;
+use std::os::raw::c_void;
+
+use super::rows::{block_stride, to_field};
+
+/// Borrow `len` values of `T` at `ptr`, or copy them if `ptr` is not aligned for `T`.
+unsafe fn slice_or_copy<'a, T: Copy>(ptr: *const u8, len: usize) -> Cow<'a, [T]> {
+    if (ptr as *const T).align_offset(std::mem::align_of::<T>()) == 0 {
+        Cow::Borrowed(std::slice::from_raw_parts(ptr as *const T, len))
+    } else {
+        Cow::Owned(
+            (0..len)
+                .map(|i| (ptr as *const T).add(i).read_unaligned())
+                .collect(),
+        )
+    }
+}
+
+/// Variable length column data (BINARY/NCHAR/JSON) in a block.
+///
+/// Each value is stored in a fixed size slot of `stride` bytes, starts with an u16 length.
+#[derive(Debug, Clone, Copy)]
+pub struct VarColumn<'a> {
+    data: &'a [u8],
+    stride: usize,
+}
+
+impl<'a> VarColumn<'a> {
+    pub(crate) fn new(data: &'a [u8], stride: usize) -> Self {
+        Self { data, stride }
+    }
+
+    /// Number of values.
+    pub fn len(&self) -> usize {
+        self.data.len().checked_div(self.stride).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The raw buffer of the column, see [VarColumn::offset] to locate a value in it.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Offset and length of the `i`th value in [VarColumn::bytes].
+    pub fn offset(&self, i: usize) -> (usize, usize) {
+        let start = i * self.stride;
+        let len = u16::from_ne_bytes([self.data[start], self.data[start + 1]]) as usize;
+        (start + 2, len)
+    }
+
+    /// Bytes of the `i`th value.
+    pub fn get(&self, i: usize) -> &'a [u8] {
+        let (offset, len) = self.offset(i);
+        &self.data[offset..offset + len]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        (0..self.len()).map(move |i| self.get(i))
+    }
+}
+
+/// Null flags of a column, one bit per row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NullBitmap {
+    bits: Vec<u8>,
+    len: usize,
+}
+
+impl NullBitmap {
+    pub(crate) fn from_fn(len: usize, is_null: impl Fn(usize) -> bool) -> Self {
+        let mut bits = vec![0u8; len.div_ceil(8)];
+        for i in (0..len).filter(|i| is_null(*i)) {
+            bits[i / 8] |= 1 << (i % 8);
+        }
+        Self { bits, len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_null(&self, i: usize) -> bool {
+        self.bits[i / 8] & (1 << (i % 8)) != 0
+    }
+
+    /// Count of null values.
+    pub fn null_count(&self) -> usize {
+        self.bits.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    /// The raw bitmap, the `i`th row is null if bit `i % 8` of byte `i / 8` is set.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bits
+    }
+}
+
+/// Typed data of a column in a block.
+///
+/// Values of null cells are undefined, check them with [Block::nulls] or [Block::is_null].
+#[derive(Debug, Clone)]
+pub enum Column<'a> {
+    Null(usize),
+    Bool(Cow<'a, [i8]>),
+    TinyInt(Cow<'a, [i8]>),
+    SmallInt(Cow<'a, [i16]>),
+    Int(Cow<'a, [i32]>),
+    BigInt(Cow<'a, [i64]>),
+    Float(Cow<'a, [f32]>),
+    Double(Cow<'a, [f64]>),
+    Binary(VarColumn<'a>),
+    Timestamp(Cow<'a, [i64]>),
+    NChar(VarColumn<'a>),
+    UTinyInt(Cow<'a, [u8]>),
+    USmallInt(Cow<'a, [u16]>),
+    UInt(Cow<'a, [u32]>),
+    UBigInt(Cow<'a, [u64]>),
+    Json(VarColumn<'a>),
+}
+
+impl<'a> Column<'a> {
+    /// Number of values.
+    pub fn len(&self) -> usize {
+        match self {
+            Column::Null(len) => *len,
+            Column::Bool(v) | Column::TinyInt(v) => v.len(),
+            Column::SmallInt(v) => v.len(),
+            Column::Int(v) => v.len(),
+            Column::BigInt(v) | Column::Timestamp(v) => v.len(),
+            Column::Float(v) => v.len(),
+            Column::Double(v) => v.len(),
+            Column::UTinyInt(v) => v.len(),
+            Column::USmallInt(v) => v.len(),
+            Column::UInt(v) => v.len(),
+            Column::UBigInt(v) => v.len(),
+            Column::Binary(v) | Column::NChar(v) | Column::Json(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A block of rows fetched by `taos_fetch_block`, in columnar layout.
+///
+/// The data is owned by the result set and only valid until the next block fetched.
+#[derive(Debug)]
+pub struct Block<'a> {
+    res: *mut TAOS_RES,
+    fields: &'a [ColumnMeta],
+    cols: &'a [*mut c_void],
+    rows: usize,
+    precision: TimestampPrecision,
+}
+
+impl<'a> Block<'a> {
+    pub fn nrows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn ncols(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn column_meta(&self) -> &'a [ColumnMeta] {
+        self.fields
+    }
+
+    pub fn precision(&self) -> TimestampPrecision {
+        self.precision
+    }
+
+    pub fn is_null(&self, row: usize, col: usize) -> bool {
+        unsafe { taos_is_null(self.res, row as _, col as _) }
+    }
+
+    /// Null bitmap of column `col`.
+    pub fn nulls(&self, col: usize) -> NullBitmap {
+        NullBitmap::from_fn(self.rows, |row| self.is_null(row, col))
+    }
+
+    /// Typed data of column `col`.
+    pub fn column(&self, col: usize) -> Column<'a> {
+        let meta = &self.fields[col];
+        let ptr = self.cols[col] as *const u8;
+        let rows = self.rows;
+        unsafe {
+            match meta.type_ {
+                TaosDataType::Null => Column::Null(rows),
+                TaosDataType::Bool => Column::Bool(slice_or_copy(ptr, rows)),
+                TaosDataType::TinyInt => Column::TinyInt(slice_or_copy(ptr, rows)),
+                TaosDataType::SmallInt => Column::SmallInt(slice_or_copy(ptr, rows)),
+                TaosDataType::Int => Column::Int(slice_or_copy(ptr, rows)),
+                TaosDataType::BigInt => Column::BigInt(slice_or_copy(ptr, rows)),
+                TaosDataType::Float => Column::Float(slice_or_copy(ptr, rows)),
+                TaosDataType::Double => Column::Double(slice_or_copy(ptr, rows)),
+                TaosDataType::Timestamp => Column::Timestamp(slice_or_copy(ptr, rows)),
+                TaosDataType::UTinyInt => Column::UTinyInt(slice_or_copy(ptr, rows)),
+                TaosDataType::USmallInt => Column::USmallInt(slice_or_copy(ptr, rows)),
+                TaosDataType::UInt => Column::UInt(slice_or_copy(ptr, rows)),
+                TaosDataType::UBigInt => Column::UBigInt(slice_or_copy(ptr, rows)),
+                TaosDataType::Binary | TaosDataType::NChar | TaosDataType::Json => {
+                    let stride = block_stride(meta);
+                    let data =
+                        VarColumn::new(std::slice::from_raw_parts(ptr, stride * rows), stride);
+                    match meta.type_ {
+                        TaosDataType::Binary => Column::Binary(data),
+                        TaosDataType::NChar => Column::NChar(data),
+                        _ => Column::Json(data),
+                    }
+                }
+                TaosDataType::Unknown => {
+                    unreachable!("unexpected data type, please contact the author to fix!")
+                }
+            }
+        }
+    }
+
+    /// Get the value at (`row`, `col`) as [Field].
+    pub fn get(&self, row: usize, col: usize) -> Field {
+        if self.is_null(row, col) {
+            return Field::Null;
+        }
+        let meta = &self.fields[col];
+        let stride = block_stride(meta);
+        unsafe {
+            let ptr = (self.cols[col] as *const u8).add(row * stride);
+            match meta.type_ {
+                TaosDataType::Binary | TaosDataType::NChar | TaosDataType::Json => {
+                    let len = (ptr as *const u16).read_unaligned() as usize;
+                    to_field(ptr.add(2) as _, meta.type_, len, self.precision)
+                }
+                TaosDataType::Null => Field::Null,
+                ty => {
+                    // copy to an aligned buffer for unaligned column data.
+                    let mut buf = 0u64;
+                    std::ptr::copy_nonoverlapping(ptr, &mut buf as *mut u64 as *mut u8, stride);
+                    to_field(&buf as *const u64 as _, ty, stride, self.precision)
+                }
+            }
+        }
+    }
+
+    /// Convert the block into rows.
+    pub fn to_rows(&self) -> Vec<Vec<Field>> {
+        (0..self.rows)
+            .map(|row| {
+                (0..self.ncols())
+                    .map(|col| self.get(row, col))
+                    .collect_vec()
+            })
+            .collect_vec()
+    }
+}
+
+impl TaosBlocks {
+    /// Fetch next block in columnar layout, `None` means all rows have been fetched.
+    ///
+    /// ```ignore
+    /// let mut blocks = taos.raw_query("select ts, v from tb1")?.blocks();
+    /// while let Some(block) = blocks.fetch_block()? {
+    ///     if let Column::Double(values) = block.column(1) {
+    ///         let nulls = block.nulls(1);
+    ///         let sum: f64 = values.iter().enumerate().filter(|(i, _)| !nulls.is_null(*i)).map(|(_, v)| v).sum();
+    ///     }
+    /// }
+    /// ```
+    pub fn fetch_block(&mut self) -> Result<Option<Block<'_>>, TaosError> {
+        let mut block: TAOS_ROW = std::ptr::null_mut();
+        let res = self.res.as_raw_mut_ptr();
+        let rows = unsafe { taos_fetch_block(res, &mut block as _) };
+        if rows <= 0 || block.is_null() {
+            let code = self.res.error_code();
+            if !code.success() {
+                return Err(self.res.check_error(TaosError {
+                    code,
+                    err: self.res.error_string().into(),
+                }));
+            }
+            return Ok(None);
+        }
+        let cols =
+            unsafe { std::slice::from_raw_parts(block as *const *mut c_void, self.fields.len()) };
+        Ok(Some(Block {
+            res,
+            fields: &self.fields,
+            cols,
+            rows: rows as _,
+            precision: self.precision,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test var column slot parsing
+    fn var_column() {
+        let mut data = vec![0u8; 12];
+        data[0..2].copy_from_slice(&3u16.to_ne_bytes());
+        data[2..5].copy_from_slice(b"abc");
+        data[6..8].copy_from_slice(&0u16.to_ne_bytes());
+        let column = VarColumn::new(&data, 6);
+        assert_eq!(column.len(), 2);
+        assert_eq!(column.get(0), b"abc");
+        assert_eq!(column.offset(1), (8, 0));
+        assert_eq!(column.iter().collect_vec(), vec![&b"abc"[..], &b""[..]]);
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test null bitmap
+    fn null_bitmap() {
+        let nulls = NullBitmap::from_fn(10, |i| i % 3 == 0);
+        assert_eq!(nulls.len(), 10);
+        assert_eq!(nulls.null_count(), 4);
+        assert!(nulls.is_null(9));
+        assert!(!nulls.is_null(8));
+        assert_eq!(nulls.as_bytes(), &[0b0100_1001, 0b10]);
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test unaligned column data is copied
+    fn unaligned_column() {
+        let values = [1i64, 2, 3];
+        let mut buf = vec![0u8; 25];
+        let unaligned = if (buf.as_ptr() as *const i64).align_offset(8) == 0 {
+            1
+        } else {
+            0
+        };
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                values.as_ptr() as *const u8,
+                buf.as_mut_ptr().add(unaligned),
+                24,
+            );
+            let column: Cow<[i64]> = slice_or_copy(buf.as_ptr().add(unaligned), 3);
+            assert!(matches!(column, Cow::Owned(_)) || unaligned == 0);
+            assert_eq!(&*column, &values);
+        }
+    }
+}
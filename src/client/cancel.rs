@@ -0,0 +1,106 @@
+use crate::bindings::*;
+use crate::*;
+
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: bool,
+    /// Result set of the running query, registered after the query started.
+    res: Option<usize>,
+    waker: Option<Waker>,
+}
+
+/// Handle to cancel a running query, could be cloned and sent to other threads.
+///
+/// ```ignore
+/// let (canceller, query) = taos.query_with_canceller("select * from m1");
+/// std::thread::spawn(move || {
+///     std::thread::sleep(Duration::from_secs(1));
+///     canceller.cancel();
+/// });
+/// let err = query.await.unwrap_err(); // query cancelled.
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Canceller {
+    state: Arc<Mutex<CancelState>>,
+}
+
+impl Canceller {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the query, it stops the query in server by `taos_stop_query` if it's running.
+    pub fn cancel(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.cancelled = true;
+        if let Some(res) = state.res {
+            unsafe { taos_stop_query(res as _) };
+        }
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.lock().unwrap().cancelled
+    }
+
+    /// The error returned by cancelled queries.
+    pub(crate) fn error() -> TaosError {
+        TaosError {
+            code: TaosCode::TscQueryCancelled,
+            err: "query cancelled".into(),
+        }
+    }
+
+    /// Wake the task when cancelled.
+    pub(crate) fn register(&self, waker: &Waker) {
+        self.state.lock().unwrap().waker = Some(waker.clone());
+    }
+
+    /// Register the result set to stop, it's stopped immediately if already cancelled.
+    pub(crate) fn attach(&self, res: *mut TAOS_RES) {
+        let mut state = self.state.lock().unwrap();
+        if state.cancelled {
+            unsafe { taos_stop_query(res) };
+        }
+        state.res = Some(res as usize);
+    }
+
+    /// Unregister the result set before it's freed.
+    pub(crate) fn detach(&self) {
+        self.state.lock().unwrap().res = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::task::{waker, ArcWake};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct Flag(AtomicBool);
+
+    impl ArcWake for Flag {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test cancel wakes the registered task
+    fn cancel_wakes() {
+        let flag = Arc::new(Flag(AtomicBool::new(false)));
+        let canceller = Canceller::new();
+        canceller.register(&waker(flag.clone()));
+        assert!(!canceller.is_cancelled());
+        canceller.clone().cancel();
+        assert!(canceller.is_cancelled());
+        assert!(flag.0.load(Ordering::SeqCst));
+        assert_eq!(Canceller::error().code, TaosCode::TscQueryCancelled);
+    }
+}
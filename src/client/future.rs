@@ -0,0 +1,260 @@
+//! Bridge the callback based `taos_query_a`/`taos_fetch_rows_a` APIs to Rust futures.
+use crate::bindings::*;
+use crate::*;
+
+use std::future::Future;
+use std::os::raw::{c_int, c_void};
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// State shared between a future and the C callback.
+#[derive(Debug)]
+struct Shared<T> {
+    slot: Mutex<Slot<T>>,
+    cond: Condvar,
+    /// The value is not wanted once cancelled.
+    canceller: Option<Canceller>,
+}
+
+#[derive(Debug)]
+struct Slot<T> {
+    value: Option<T>,
+    waker: Option<Waker>,
+    /// The future was dropped before the callback.
+    dropped: bool,
+    /// The callback has been called.
+    done: bool,
+}
+
+impl<T> Shared<T> {
+    fn new() -> Arc<Self> {
+        Self::with_canceller(None)
+    }
+
+    fn with_canceller(canceller: Option<Canceller>) -> Arc<Self> {
+        Arc::new(Self {
+            slot: Mutex::new(Slot {
+                value: None,
+                waker: None,
+                dropped: false,
+                done: false,
+            }),
+            cond: Condvar::new(),
+            canceller,
+        })
+    }
+
+    /// Leak a reference as callback param, the callback will take it back.
+    fn into_param(self: &Arc<Self>) -> *mut c_void {
+        Arc::into_raw(self.clone()) as _
+    }
+
+    /// Called in callback, returns the value back if the future was dropped or cancelled.
+    unsafe fn complete(param: *mut c_void, value: T) -> Option<T> {
+        let shared = Arc::from_raw(param as *const Self);
+        let mut slot = shared.slot.lock().unwrap();
+        slot.done = true;
+        shared.cond.notify_all();
+        let cancelled = shared
+            .canceller
+            .as_ref()
+            .map(|canceller| canceller.is_cancelled())
+            .unwrap_or(false);
+        if slot.dropped || cancelled {
+            return Some(value);
+        }
+        slot.value = Some(value);
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+        None
+    }
+
+    fn poll(&self, cx: &mut Context<'_>) -> Poll<T> {
+        let mut slot = self.slot.lock().unwrap();
+        match slot.value.take() {
+            Some(value) => Poll::Ready(value),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Mark as dropped, returns the value if it's already completed but not polled.
+    fn drop_value(&self) -> Option<T> {
+        let mut slot = self.slot.lock().unwrap();
+        slot.dropped = true;
+        slot.value.take()
+    }
+
+    /// Block until the callback has been called.
+    fn wait(&self) {
+        let mut slot = self.slot.lock().unwrap();
+        while !slot.done {
+            slot = self.cond.wait(slot).unwrap();
+        }
+    }
+}
+
+struct RawRes(*mut TAOS_RES);
+
+unsafe impl Send for RawRes {}
+
+/// Stop the query in server and free the result set nobody waits for.
+unsafe fn abandon(res: *mut TAOS_RES) {
+    taos_stop_query(res);
+    taos_free_result(res);
+}
+
+unsafe extern "C" fn query_callback(param: *mut c_void, res: *mut TAOS_RES, _code: c_int) {
+    if let Some(RawRes(res)) = Shared::complete(param, RawRes(res)) {
+        abandon(res);
+    }
+}
+
+/// Future of an async query by `taos_query_a`, resolves to the result set.
+///
+/// The client library provides no handle to stop a query before `taos_query_a` calls back, so
+/// dropping the future or cancelling it by [Canceller] before it's ready returns immediately, but
+/// the query is only stopped by `taos_stop_query` and freed once the callback is called.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct QueryFuture<'a> {
+    shared: Arc<Shared<RawRes>>,
+    canceller: Canceller,
+    taos: &'a Taos,
+}
+
+impl<'a> QueryFuture<'a> {
+    pub(crate) fn new(taos: &'a Taos, sql: impl ToCString, canceller: Canceller) -> Self {
+        let sql = sql.to_c_string();
+        let shared = Shared::with_canceller(Some(canceller.clone()));
+        unsafe {
+            taos_query_a(
+                taos.as_raw(),
+                sql.as_ptr(),
+                Some(query_callback),
+                shared.into_param(),
+            )
+        };
+        Self {
+            shared,
+            canceller,
+            taos,
+        }
+    }
+
+    /// Handle to cancel the query.
+    pub fn canceller(&self) -> Canceller {
+        self.canceller.clone()
+    }
+}
+
+impl<'a> Future for QueryFuture<'a> {
+    type Output = Result<CTaosResult, TaosError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.canceller.is_cancelled() {
+            return Poll::Ready(Err(Canceller::error()));
+        }
+        self.canceller.register(cx.waker());
+        let taos = self.taos;
+        self.shared.poll(cx).map(|RawRes(res)| {
+            CTaosResult::new(res)
+                .map(|res| res.watch(taos.broken_flag()))
+                .map_err(|err| taos.check_error(err))
+        })
+    }
+}
+
+impl<'a> Drop for QueryFuture<'a> {
+    fn drop(&mut self) {
+        if let Some(RawRes(res)) = self.shared.drop_value() {
+            unsafe { abandon(res) };
+        }
+    }
+}
+
+unsafe extern "C" fn fetch_callback(param: *mut c_void, res: *mut TAOS_RES, rows: c_int) {
+    if Shared::complete(param, rows).is_some() {
+        // the rows were dropped while fetching, the result set is handed over to us.
+        taos_free_result(res);
+    }
+}
+
+/// An in-flight `taos_fetch_rows_a` call of a result set.
+#[derive(Debug)]
+pub(crate) struct FetchRows {
+    shared: Arc<Shared<c_int>>,
+}
+
+impl FetchRows {
+    /// Start fetching next block of rows.
+    pub(crate) fn start(res: *mut TAOS_RES) -> Self {
+        let shared = Shared::new();
+        unsafe { taos_fetch_rows_a(res, Some(fetch_callback), shared.into_param()) };
+        Self { shared }
+    }
+
+    /// Poll for number of rows fetched, `0` means no more rows and negative is an error code.
+    pub(crate) fn poll(&self, cx: &mut Context<'_>) -> Poll<c_int> {
+        self.shared.poll(cx)
+    }
+
+    /// Block until rows fetched.
+    pub(crate) fn wait(self) -> c_int {
+        self.shared.wait();
+        self.shared
+            .slot
+            .lock()
+            .unwrap()
+            .value
+            .take()
+            .unwrap_or_default()
+    }
+
+    /// Stop the query without waiting, returns `false` if the callback has been called, or the
+    /// callback will free the result set and it must not be freed by the caller.
+    pub(crate) fn cancel(self, res: *mut TAOS_RES) -> bool {
+        let mut slot = self.shared.slot.lock().unwrap();
+        slot.dropped = true;
+        if slot.done {
+            return false;
+        }
+        unsafe { taos_stop_query(res) };
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test values completed after dropped or cancelled are handed back to the callback
+    fn complete_abandoned() {
+        let canceller = Canceller::new();
+        let shared = Shared::<i32>::with_canceller(Some(canceller.clone()));
+        assert_eq!(
+            unsafe { Shared::<i32>::complete(shared.into_param(), 1) },
+            None
+        );
+        assert_eq!(shared.drop_value(), Some(1));
+
+        let shared = Shared::<i32>::with_canceller(Some(canceller.clone()));
+        canceller.cancel();
+        assert_eq!(
+            unsafe { Shared::<i32>::complete(shared.into_param(), 2) },
+            Some(2)
+        );
+
+        let shared = Shared::<i32>::new();
+        assert_eq!(shared.drop_value(), None);
+        assert_eq!(
+            unsafe { Shared::<i32>::complete(shared.into_param(), 3) },
+            Some(3)
+        );
+    }
+}
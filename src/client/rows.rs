@@ -0,0 +1,260 @@
+use crate::*;
+
+use std::os::raw::{c_int, c_void};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+
+use super::future::FetchRows;
+
+/// Convert a raw cell pointer to [Field], `len` is only used by variable length types.
+pub(crate) unsafe fn to_field(
+    ptr: *const c_void,
+    ty: TaosDataType,
+    len: usize,
+    precision: TimestampPrecision,
+) -> Field {
+    match ty {
+        TaosDataType::Null => Field::Null,
+        TaosDataType::Bool => Field::Bool(*(ptr as *const i8) != 0),
+        TaosDataType::TinyInt => Field::TinyInt(*(ptr as *const i8)),
+        TaosDataType::SmallInt => Field::SmallInt(*(ptr as *const i16)),
+        TaosDataType::Int => Field::Int(*(ptr as *const i32)),
+        TaosDataType::BigInt => Field::BigInt(*(ptr as *const i64)),
+        TaosDataType::UTinyInt => Field::UTinyInt(*(ptr as *const u8)),
+        TaosDataType::USmallInt => Field::USmallInt(*(ptr as *const u16)),
+        TaosDataType::UInt => Field::UInt(*(ptr as *const u32)),
+        TaosDataType::UBigInt => Field::UBigInt(*(ptr as *const u64)),
+        TaosDataType::Timestamp => {
+            Field::Timestamp(Timestamp::new(*(ptr as *const i64), precision))
+        }
+        TaosDataType::Float => Field::Float(*(ptr as *const f32)),
+        TaosDataType::Double => Field::Double(*(ptr as *const f64)),
+        TaosDataType::Binary => {
+            Field::Binary(std::slice::from_raw_parts(ptr as *const u8, len).into())
+        }
+        TaosDataType::NChar => {
+            let slice = std::slice::from_raw_parts(ptr as *const u8, len);
+            Field::NChar(String::from_utf8_lossy(slice).to_string())
+        }
+        TaosDataType::Json => {
+            let slice = std::slice::from_raw_parts(ptr as *const u8, len);
+            serde_json::from_slice(slice)
+                .ok()
+                .map(Field::Json)
+                .unwrap_or(Field::Null)
+        }
+        _ => {
+            unreachable!("unexpected data type, please contact the author to fix!")
+        }
+    }
+}
+
+/// Bytes of each cell of a column in a block fetched by `taos_fetch_block`.
+///
+/// Variable length values are prefixed by an u16 length, and NCHAR/JSON are
+/// reserved 4 bytes per character.
+pub(crate) fn block_stride(meta: &ColumnMeta) -> usize {
+    match meta.type_ {
+        TaosDataType::Null => 0,
+        TaosDataType::Bool | TaosDataType::TinyInt | TaosDataType::UTinyInt => 1,
+        TaosDataType::SmallInt | TaosDataType::USmallInt => 2,
+        TaosDataType::Int | TaosDataType::UInt | TaosDataType::Float => 4,
+        TaosDataType::BigInt
+        | TaosDataType::UBigInt
+        | TaosDataType::Double
+        | TaosDataType::Timestamp => 8,
+        TaosDataType::Binary => meta.bytes as usize + 2,
+        TaosDataType::NChar | TaosDataType::Json => meta.bytes as usize * 4 + 2,
+        TaosDataType::Unknown => {
+            unreachable!("unexpected data type, please contact the author to fix!")
+        }
+    }
+}
+
+/// Lazy row iterator owning the result set, which is freed on drop.
+///
+/// It could also be used as a [Stream] of rows, which fetches rows by `taos_fetch_rows_a`
+/// without blocking.
+#[derive(Debug)]
+pub struct TaosRows {
+    res: CTaosResult,
+    fields: Vec<ColumnMeta>,
+    precision: TimestampPrecision,
+    done: bool,
+    /// Rows fetched by `taos_fetch_rows_a` but not consumed.
+    buffered: usize,
+    fetching: Option<FetchRows>,
+    canceller: Canceller,
+}
+
+impl TaosRows {
+    pub(crate) fn new(res: CTaosResult) -> Self {
+        Self::with_canceller(res, Canceller::new())
+    }
+
+    pub(crate) fn with_canceller(mut res: CTaosResult, canceller: Canceller) -> Self {
+        let fields = res.column_meta();
+        let precision = res.precision();
+        canceller.attach(res.as_raw_mut_ptr());
+        Self {
+            res,
+            fields,
+            precision,
+            done: false,
+            buffered: 0,
+            fetching: None,
+            canceller,
+        }
+    }
+
+    /// Handle to stop fetching rows.
+    pub fn canceller(&self) -> Canceller {
+        self.canceller.clone()
+    }
+
+    pub fn column_meta(&self) -> &[ColumnMeta] {
+        &self.fields
+    }
+
+    fn next_row(&mut self) -> Option<Result<Vec<Field>, TaosError>> {
+        let row = self.res.fetch_row(&self.fields, self.precision).transpose();
+        if !matches!(row, Some(Ok(_))) {
+            self.done = true;
+        }
+        row
+    }
+
+    pub fn precision(&self) -> TimestampPrecision {
+        self.precision
+    }
+
+    /// Handle number of rows fetched by `taos_fetch_rows_a`.
+    fn fetched(&mut self, num: c_int) -> Result<(), TaosError> {
+        match num {
+            0 => self.done = true,
+            num if num < 0 => {
+                self.done = true;
+                return Err(self.res.check_error(TaosError {
+                    code: (num & 0x0000ffff).into(),
+                    err: self.res.error_string().into(),
+                }));
+            }
+            num => self.buffered = num as _,
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for TaosRows {
+    type Item = Result<Vec<Field>, TaosError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.canceller.is_cancelled() {
+            self.done = true;
+            return Some(Err(Canceller::error()));
+        }
+        if let Some(fetching) = self.fetching.take() {
+            if let Err(err) = self.fetched(fetching.wait()) {
+                return Some(Err(err));
+            }
+            if self.done {
+                return None;
+            }
+        }
+        self.buffered = self.buffered.saturating_sub(1);
+        self.next_row()
+    }
+}
+
+impl Stream for TaosRows {
+    type Item = Result<Vec<Field>, TaosError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let rows = self.get_mut();
+        loop {
+            if rows.done {
+                return Poll::Ready(None);
+            }
+            if rows.canceller.is_cancelled() {
+                rows.done = true;
+                return Poll::Ready(Some(Err(Canceller::error())));
+            }
+            if rows.buffered > 0 {
+                // rows are already fetched, taos_fetch_row will not block.
+                rows.buffered -= 1;
+                return Poll::Ready(rows.next_row());
+            }
+            let res = rows.res.as_raw_mut_ptr();
+            let fetching = rows.fetching.get_or_insert_with(|| FetchRows::start(res));
+            rows.canceller.register(cx.waker());
+            let num = futures::ready!(fetching.poll(cx));
+            rows.fetching = None;
+            if let Err(err) = rows.fetched(num) {
+                return Poll::Ready(Some(Err(err)));
+            }
+        }
+    }
+}
+
+impl Drop for TaosRows {
+    fn drop(&mut self) {
+        // detach first, so the canceller would not stop a freed result set.
+        self.canceller.detach();
+        if let Some(fetching) = self.fetching.take() {
+            if fetching.cancel(self.res.as_raw_mut_ptr()) {
+                self.res.release();
+            }
+        }
+    }
+}
+
+/// Iterator of row blocks fetched by `taos_fetch_block`, owning the result set.
+///
+/// Use [TaosBlocks::fetch_block] to access the columnar data without converting to rows.
+#[derive(Debug)]
+pub struct TaosBlocks {
+    pub(super) res: CTaosResult,
+    pub(super) fields: Vec<ColumnMeta>,
+    pub(super) precision: TimestampPrecision,
+    done: bool,
+}
+
+impl TaosBlocks {
+    pub(crate) fn new(res: CTaosResult) -> Self {
+        let fields = res.column_meta();
+        let precision = res.precision();
+        Self {
+            res,
+            fields,
+            precision,
+            done: false,
+        }
+    }
+
+    pub fn column_meta(&self) -> &[ColumnMeta] {
+        &self.fields
+    }
+}
+
+impl Iterator for TaosBlocks {
+    type Item = Result<Vec<Vec<Field>>, TaosError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let block = self
+            .fetch_block()
+            .map(|block| block.map(|block| block.to_rows()))
+            .transpose();
+        if !matches!(block, Some(Ok(_))) {
+            self.done = true;
+        }
+        block
+    }
+}
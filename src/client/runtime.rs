@@ -0,0 +1,162 @@
+//! Lifecycle of the native client library.
+use crate::bindings::*;
+use crate::*;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+lazy_static::lazy_static! {
+    /// The alive runtime, connections keep it alive by strong references.
+    static ref RUNTIME: Mutex<Weak<TaosRuntime>> = Mutex::new(Weak::new());
+}
+
+/// Whether the library has been cleaned up by [TaosRuntime::shutdown].
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+
+/// Guard of the client library, initialized by `taos_init` when created.
+///
+/// Each connection holds a reference of the runtime. The library is never cleaned up implicitly,
+/// since `taos_init` runs only once in a process and could not re-initialize a cleaned up library,
+/// e.g. when all connections of a pool are recycled and created again. With feature `cleanup`,
+/// call [TaosRuntime::shutdown] after all connections closed to release the library.
+///
+/// ```ignore
+/// let runtime = TaosRuntime::with_options(options)?;
+/// let taos = runtime.connect("localhost", "root", "taosdata", "log", 6030)?;
+/// ```
+#[derive(Debug)]
+pub struct TaosRuntime {
+    options: Option<ClientOptions>,
+}
+
+impl TaosRuntime {
+    fn get_or_init(options: Option<ClientOptions>) -> Result<Arc<TaosRuntime>, Error> {
+        let mut runtime = RUNTIME.lock().unwrap();
+        if let Some(runtime) = runtime.upgrade() {
+            if let Some(options) = &options {
+                // fails if options not the same as the applied one.
+                options.apply()?;
+            }
+            return Ok(runtime);
+        }
+        if SHUTDOWN.load(Ordering::SeqCst) {
+            return Err(Error::RuntimeShutdown);
+        }
+        if let Some(options) = &options {
+            options.apply()?;
+        }
+        let code = unsafe { taos_init() };
+        if code != 0 {
+            return Err(TaosError {
+                code: (code & 0x0000ffff).into(),
+                err: "failed to initialize client library".into(),
+            }
+            .into());
+        }
+        let rt = Arc::new(TaosRuntime { options });
+        *runtime = Arc::downgrade(&rt);
+        Ok(rt)
+    }
+
+    /// Get the alive runtime or initialize a new one.
+    pub fn init() -> Result<Arc<TaosRuntime>, Error> {
+        Self::get_or_init(None)
+    }
+
+    /// Initialize runtime with client options, see [ClientOptions::apply].
+    pub fn with_options(options: ClientOptions) -> Result<Arc<TaosRuntime>, Error> {
+        Self::get_or_init(Some(options))
+    }
+
+    /// Clean up the library by `taos_cleanup`, fails if any runtime or connection is alive.
+    ///
+    /// The library could not be initialized again in the process, so following connections fail
+    /// with [Error::RuntimeShutdown].
+    #[cfg(feature = "cleanup")]
+    pub fn shutdown() -> Result<(), Error> {
+        let runtime = RUNTIME.lock().unwrap();
+        if runtime.upgrade().is_some() {
+            return Err(TaosError {
+                code: TaosCode::TscAppError,
+                err: "client library is still in use".into(),
+            }
+            .into());
+        }
+        if !SHUTDOWN.swap(true, Ordering::SeqCst) {
+            unsafe { taos_cleanup() };
+        }
+        Ok(())
+    }
+
+    /// The alive runtime, if any.
+    pub fn current() -> Option<Arc<TaosRuntime>> {
+        RUNTIME.lock().unwrap().upgrade()
+    }
+
+    /// Client options applied by this runtime.
+    pub fn options(&self) -> Option<&ClientOptions> {
+        self.options.as_ref()
+    }
+
+    /// Connect to server, the connection keeps a reference of the runtime.
+    ///
+    /// Returns [Error::IncompatibleVersion] if major versions of client and server differ, the check
+    /// is skipped if either version could not be parsed.
+    pub fn connect(
+        self: &Arc<Self>,
+        ip: impl ToCString,
+        user: impl ToCString,
+        pass: impl ToCString,
+        db: impl ToCString,
+        port: u16,
+    ) -> Result<Taos, Error> {
+        let ip = ip.to_c_string();
+        let user = user.to_c_string();
+        let pass = pass.to_c_string();
+        let db = db.to_c_string();
+
+        // options could not be changed after connected, see `ClientOptions::apply`.
+        options::connecting();
+        let conn =
+            unsafe { taos_connect(ip.as_ptr(), user.as_ptr(), pass.as_ptr(), db.as_ptr(), port) };
+        if conn.is_null() {
+            return Err(Error::ConnectionInvalid);
+        }
+        let taos = Taos::from_raw(conn, self.clone());
+        // only a known major version mismatch blocks connecting.
+        match (client_version(), taos.server_version()) {
+            (Ok(client), Ok(server)) => version::check_compatible(client, server)?,
+            (client, server) => warn!(
+                "skip version compatibility check, client: {:?}, server: {:?}",
+                client, server
+            ),
+        }
+        Ok(taos)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test runtime is shared while alive
+    fn shared_runtime() -> Result<(), Error> {
+        let runtime = TaosRuntime::init()?;
+        assert!(Arc::ptr_eq(&runtime, &TaosRuntime::init()?));
+        assert!(Arc::ptr_eq(&runtime, &TaosRuntime::current().unwrap()));
+        Ok(())
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test connecting again after all connections and runtimes dropped
+    fn reconnect_runtime() -> Result<(), Error> {
+        let taos = crate::test::taos()?;
+        drop(taos);
+        let taos = crate::test::taos()?;
+        assert!(taos.server_version().is_ok());
+        Ok(())
+    }
+}
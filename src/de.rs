@@ -0,0 +1,402 @@
+//! Deserialize query rows into user defined types with serde.
+//!
+//! Each row is exposed as a map keyed by [ColumnMeta::name], so a struct
+//! deriving `Deserialize` can be filled by column name, while tuples are filled
+//! by column position.
+//!
+//! ```ignore
+//! #[derive(Deserialize)]
+//! struct Record {
+//!     ts: Timestamp,
+//!     speed: Option<u32>,
+//! }
+//! let records: Vec<Record> = taos.query_as("select * from m1").await?;
+//! ```
+use std::fmt::{self, Display};
+
+use serde::de::{
+    self, value::BorrowedStrDeserializer, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess,
+    Visitor,
+};
+use serde::forward_to_deserialize_any;
+use thiserror::Error;
+
+use crate::field::*;
+
+/// Name used by `Timestamp` to ask a deserializer for its raw representation.
+pub(crate) const TIMESTAMP_NEWTYPE: &str = "$libtaos::Timestamp";
+
+/// Error raised when a row could not be deserialized into the target type.
+#[derive(Debug, Error)]
+pub struct DeError(String);
+
+impl Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl de::Error for DeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        DeError(msg.to_string())
+    }
+}
+
+/// Deserializer over one row of a query result.
+pub struct RowDeserializer<'de> {
+    meta: &'de [ColumnMeta],
+    row: &'de [Field],
+}
+
+impl<'de> RowDeserializer<'de> {
+    pub fn new(meta: &'de [ColumnMeta], row: &'de [Field]) -> Self {
+        Self { meta, row }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for RowDeserializer<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(RowSeqAccess {
+            iter: self.row.iter(),
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(RowMapAccess {
+            iter: self.meta.iter().zip(self.row.iter()),
+            value: None,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct enum identifier ignored_any
+    }
+}
+
+struct RowSeqAccess<'de> {
+    iter: std::slice::Iter<'de, Field>,
+}
+
+impl<'de> SeqAccess<'de> for RowSeqAccess<'de> {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        match self.iter.next() {
+            Some(field) => seed.deserialize(FieldDeserializer::new(field)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct RowMapAccess<'de, I> {
+    iter: I,
+    value: Option<&'de Field>,
+}
+
+impl<'de, I> MapAccess<'de> for RowMapAccess<'de, I>
+where
+    I: Iterator<Item = (&'de ColumnMeta, &'de Field)>,
+{
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.iter.next() {
+            Some((meta, field)) => {
+                self.value = Some(field);
+                seed.deserialize(BorrowedStrDeserializer::new(&meta.name))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let field = self
+            .value
+            .take()
+            .ok_or_else(|| <DeError as de::Error>::custom("value is missing"))?;
+        seed.deserialize(FieldDeserializer::new(field))
+    }
+}
+
+/// Deserializer over a single [Field] value.
+pub struct FieldDeserializer<'de>(&'de Field);
+
+impl<'de> FieldDeserializer<'de> {
+    pub fn new(field: &'de Field) -> Self {
+        Self(field)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for FieldDeserializer<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Field::Null => visitor.visit_unit(),
+            Field::Bool(v) => visitor.visit_bool(*v),
+            Field::TinyInt(v) => visitor.visit_i8(*v),
+            Field::SmallInt(v) => visitor.visit_i16(*v),
+            Field::Int(v) => visitor.visit_i32(*v),
+            Field::BigInt(v) => visitor.visit_i64(*v),
+            Field::UTinyInt(v) => visitor.visit_u8(*v),
+            Field::USmallInt(v) => visitor.visit_u16(*v),
+            Field::UInt(v) => visitor.visit_u32(*v),
+            Field::UBigInt(v) => visitor.visit_u64(*v),
+            Field::Float(v) => visitor.visit_f32(*v),
+            Field::Double(v) => visitor.visit_f64(*v),
+            Field::Binary(v) => match std::str::from_utf8(v) {
+                Ok(s) => visitor.visit_borrowed_str(s),
+                Err(_) => visitor.visit_borrowed_bytes(v),
+            },
+            Field::NChar(v) => visitor.visit_borrowed_str(v),
+            Field::Timestamp(v) => visitor.visit_i64(v.as_raw_timestamp()),
+            Field::Json(v) => v.deserialize_any(visitor).map_err(de::Error::custom),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Field::Null | Field::Json(serde_json::Value::Null) => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Field::Timestamp(v) => visitor.visit_string(v.to_string()),
+            Field::Json(serde_json::Value::String(v)) => visitor.visit_borrowed_str(v),
+            Field::Json(v) => visitor.visit_string(v.to_string()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Field::Binary(v) => visitor.visit_borrowed_bytes(v),
+            Field::NChar(v) => visitor.visit_borrowed_bytes(v.as_bytes()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Field::Timestamp(v) if name == TIMESTAMP_NEWTYPE => {
+                let raw = vec![v.as_raw_timestamp(), v.precision as i64];
+                visitor.visit_seq(raw.into_deserializer())
+            }
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Field::NChar(v) => visitor.visit_enum(BorrowedStrDeserializer::new(v)),
+            Field::Binary(v) => match std::str::from_utf8(v) {
+                Ok(s) => visitor.visit_enum(BorrowedStrDeserializer::new(s)),
+                Err(_) => self.deserialize_any(visitor),
+            },
+            Field::Json(v) => v
+                .deserialize_enum(name, variants, visitor)
+                .map_err(de::Error::custom),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char
+        unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl TaosQueryData {
+    /// Deserialize each row into `T`, see [crate::de] for the mapping rules.
+    pub fn deserialize<'de, T: de::Deserialize<'de>>(
+        &'de self,
+    ) -> impl Iterator<Item = Result<T, DeError>> + 'de {
+        self.rows
+            .iter()
+            .map(move |row| T::deserialize(RowDeserializer::new(&self.column_meta, row)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use bstr::BString;
+    use serde::Deserialize;
+
+    use crate::*;
+
+    fn meta(name: &str, type_: TaosDataType) -> ColumnMeta {
+        ColumnMeta {
+            name: name.to_string(),
+            type_,
+            bytes: 0,
+        }
+    }
+
+    fn data() -> TaosQueryData {
+        TaosQueryData {
+            column_meta: vec![
+                meta("ts", TaosDataType::Timestamp),
+                meta("speed", TaosDataType::UInt),
+                meta("location", TaosDataType::Binary),
+                meta("name", TaosDataType::NChar),
+                meta("jtag", TaosDataType::Json),
+            ],
+            rows: vec![
+                vec![
+                    Field::Timestamp(Timestamp::new(1626006833639, TimestampPrecision::Milli)),
+                    Field::UInt(10),
+                    Field::Binary("beijing".into()),
+                    Field::NChar("涛思数据".into()),
+                    Field::Json(serde_json::json!({"tag1": "fff"})),
+                ],
+                vec![
+                    Field::Timestamp(Timestamp::new(1626006833640000, TimestampPrecision::Micro)),
+                    Field::Null,
+                    Field::Binary("shanghai".into()),
+                    Field::NChar("taosdata".into()),
+                    Field::Null,
+                ],
+            ],
+        }
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test deserialize rows into struct by column names
+    fn de_struct() {
+        #[derive(Debug, Deserialize)]
+        struct Record {
+            ts: Timestamp,
+            speed: Option<u32>,
+            location: BString,
+            name: String,
+            jtag: Option<serde_json::Value>,
+        }
+        let data = data();
+        let records: Vec<Record> = data.deserialize().collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0].ts,
+            Timestamp::new(1626006833639, TimestampPrecision::Milli)
+        );
+        assert_eq!(records[0].speed, Some(10));
+        assert_eq!(records[0].location, "beijing");
+        assert_eq!(records[0].name, "涛思数据");
+        assert_eq!(records[0].jtag, Some(serde_json::json!({"tag1": "fff"})));
+        assert_eq!(
+            records[1].ts,
+            Timestamp::new(1626006833640000, TimestampPrecision::Micro)
+        );
+        assert_eq!(records[1].speed, None);
+        assert_eq!(records[1].jtag, None);
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test deserialize rows into tuples, borrowed strings and maps
+    fn de_tuple_and_map() {
+        let data = data();
+        let rows: Vec<(i64, Option<u32>, &str, &str)> =
+            data.deserialize().collect::<Result<_, _>>().unwrap();
+        assert_eq!(rows[0], (1626006833639, Some(10), "beijing", "涛思数据"));
+
+        let rows: Vec<HashMap<String, serde_json::Value>> =
+            data.deserialize().collect::<Result<_, _>>().unwrap();
+        assert_eq!(rows[1]["speed"], serde_json::Value::Null);
+        assert_eq!(rows[1]["location"], serde_json::json!("shanghai"));
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test type mismatch is reported as error
+    fn de_error() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Record {
+            location: i32,
+        }
+        let data = data();
+        let res: Result<Vec<Record>, _> = data.deserialize().collect();
+        assert!(res.is_err());
+    }
+}
@@ -15,6 +15,16 @@ pub struct ColumnMeta {
     pub type_: TaosDataType,
     pub bytes: i16,
 }
+
+impl ColumnMeta {
+    pub fn new(name: impl Into<String>, type_: TaosDataType, bytes: i16) -> Self {
+        Self {
+            name: name.into(),
+            type_,
+            bytes,
+        }
+    }
+}
 #[derive(Debug)]
 pub struct TaosQueryData {
     pub column_meta: Vec<ColumnMeta>,
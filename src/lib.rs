@@ -8,35 +8,64 @@ use itertools::Itertools;
 use log::*;
 use thiserror::Error;
 
-#[cfg(not(feature = "rest"))]
+#[cfg(feature = "native")]
 pub mod bindings;
 
-#[cfg(not(feature = "rest"))]
+#[cfg(feature = "native")]
 mod util;
-#[cfg(not(feature = "rest"))]
+#[cfg(feature = "native")]
 pub(crate) use util::*;
 
+mod backend;
+pub use backend::*;
+pub mod de;
+pub mod dsn;
+pub mod meta;
+pub use meta::{DatabaseInfo, StableInfo, TableInfo, TagValue};
+pub mod options;
+pub use options::{ClientOptions, ClientOptionsBuilder};
+pub mod schema;
+pub use schema::{SchemaChange, StableSchema};
 mod error;
+pub mod sql;
 mod timestamp;
 pub use timestamp::*;
+mod version;
+pub use version::*;
 
 pub mod field;
 #[cfg(feature = "rest")]
-mod rest;
+pub mod rest;
+#[cfg(all(feature = "rest", not(feature = "native")))]
+pub use rest::Taos;
 #[cfg(feature = "rest")]
-pub use rest::*;
+pub use rest::Taos as RestTaos;
 
-#[cfg(not(feature = "rest"))]
+#[cfg(feature = "native")]
 mod client;
-#[cfg(not(feature = "rest"))]
+#[cfg(feature = "native")]
 pub use client::*;
 
-#[cfg(all(not(feature = "rest"), feature = "stmt"))]
+#[cfg(all(feature = "native", feature = "stmt"))]
 pub mod stmt;
 
-#[cfg(all(not(feature = "rest"), feature = "schemaless"))]
+#[cfg(all(feature = "native", feature = "stmt"))]
+pub mod bulk;
+
+#[cfg(feature = "native")]
+pub mod stream;
+
+#[cfg(feature = "native")]
+pub mod subscribe;
+
 pub mod schemaless;
 
+#[cfg(any(feature = "r2d2", feature = "bb8"))]
+pub mod pool;
+#[cfg(feature = "r2d2")]
+pub use pool::TaosPool;
+
+pub use de::DeError;
 pub use error::*;
 pub use field::*;
 
@@ -46,6 +75,36 @@ pub enum Error {
     ConnectionInvalid,
     #[error("taos error: {0}")]
     RawTaosError(#[from] TaosError),
+    #[error("deserialize error: {0}")]
+    DeserializeError(#[from] DeError),
+    #[error("query timeout after {0:?}")]
+    Timeout(std::time::Duration),
+    #[error("invalid identifier: {0:?}")]
+    InvalidIdentifier(String),
+    #[error("invalid version: {0:?}")]
+    InvalidVersion(String),
+    #[error("client version {client} is incompatible with server version {server}")]
+    IncompatibleVersion { client: Version, server: Version },
+    #[error("invalid client options: {0}")]
+    InvalidOptions(String),
+    #[error("client library has been cleaned up")]
+    RuntimeShutdown,
+    #[error("invalid schema: {0}")]
+    InvalidSchema(String),
+    #[error("incompatible schema change: {0}")]
+    IncompatibleSchema(String),
+    #[error("invalid schemaless data: {0}")]
+    InvalidSchemaless(String),
+    #[error("bulk writer is closed")]
+    WriterClosed,
+    #[error("invalid sql literal: {0}")]
+    InvalidLiteral(String),
+    #[error("invalid dsn: {0}")]
+    InvalidDsn(String),
+    #[error("backend {0:?} is not enabled")]
+    BackendUnavailable(Backend),
+    #[error("{0} is not supported by {1:?} backend")]
+    Unsupported(&'static str, Backend),
     #[cfg(feature = "rest")]
     #[error("rest error: {0}")]
     RestApiError(#[from] reqwest::Error),
@@ -63,7 +122,38 @@ impl Display for TaosError {
     }
 }
 
-#[derive(Builder, Debug)]
+impl TaosError {
+    /// Whether the error means the connection is no longer usable.
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self.code,
+            TaosCode::RpcNetworkUnavail
+                | TaosCode::RpcAuthFailure
+                | TaosCode::RpcFqdnError
+                | TaosCode::TscInvalidConnection
+                | TaosCode::TscDisconnected
+                | TaosCode::TscConnKilled
+                | TaosCode::MndInvalidConnection
+                | TaosCode::MndInvalidConnId
+        )
+    }
+}
+
+impl Error {
+    /// Whether the error means the connection is no longer usable.
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            Error::ConnectionInvalid => true,
+            Error::RawTaosError(err) => err.is_fatal(),
+            #[cfg(feature = "rest")]
+            Error::RestApiError(err) => err.is_connect(),
+            _ => false,
+        }
+    }
+}
+
+/// Connection config, built by [TaosCfgBuilder] or parsed from DSN by [TaosCfg::from_dsn].
+#[derive(Builder, Debug, Clone)]
 #[builder(setter(into))]
 pub struct TaosCfg {
     ip: String,
@@ -72,49 +162,103 @@ pub struct TaosCfg {
     #[builder(setter(strip_option))]
     db: Option<String>,
     port: u16,
+    /// Backend used by [TaosCfg::connect_client], native by default if available.
+    #[builder(default)]
+    backend: Backend,
+    /// Port of REST API, the server port plus 11 if not set.
+    #[builder(default, setter(strip_option))]
+    rest_port: Option<u16>,
+    /// Use https for REST API.
+    #[builder(default)]
+    tls: bool,
+    /// Extra parameters in DSN query string.
+    #[builder(default)]
+    params: std::collections::BTreeMap<String, String>,
+    /// Client library options, applied before the first native connection.
+    #[builder(default, setter(strip_option))]
+    options: Option<ClientOptions>,
 }
 
 impl TaosCfg {
+    /// Connect with REST API.
     #[cfg(feature = "rest")]
-    pub fn connect(&self) -> Result<Taos, Error> {
-        Ok(Taos::new(
-            format!("http://{}:{}/rest/sql", self.ip, self.port + 11),
+    pub fn connect_rest(&self) -> Result<RestTaos, Error> {
+        let taos = RestTaos::new(
+            format!(
+                "{}://{}:{}/rest/sql",
+                if self.tls { "https" } else { "http" },
+                self.ip,
+                self.rest_port()?
+            ),
             self.user.clone(),
             self.pass.clone(),
-        ))
+        );
+        Ok(match &self.db {
+            Some(db) => taos.with_database(db),
+            None => taos,
+        })
     }
 
-    #[cfg(not(feature = "rest"))]
-    pub fn connect(&self) -> Result<Taos, Error> {
+    /// Client options set by builder, or parsed from DSN parameters.
+    pub fn client_options(&self) -> Result<Option<ClientOptions>, Error> {
+        match &self.options {
+            Some(options) => Ok(Some(options.clone())),
+            None => ClientOptions::from_params(&self.params),
+        }
+    }
+
+    /// Connect with native client library.
+    #[cfg(feature = "native")]
+    pub fn connect_native(&self) -> Result<Taos, Error> {
+        let runtime = match self.client_options()? {
+            Some(options) => TaosRuntime::with_options(options)?,
+            None => TaosRuntime::init()?,
+        };
         let default_db = "log".to_string();
-        Taos::new(
-            &self.ip,
+        runtime.connect(
+            self.native_host(),
             &self.user,
             &self.pass,
             self.db.as_ref().unwrap_or(&default_db),
             self.port,
         )
     }
-}
 
-#[cfg(feature = "r2d2")]
-pub type TaosPool = r2d2::Pool<TaosCfg>;
-
-#[cfg(feature = "r2d2")]
-impl r2d2::ManageConnection for TaosCfg {
-    type Connection = Taos;
-    type Error = Error;
-
-    fn connect(&self) -> Result<Self::Connection, Self::Error> {
-        self.connect()
+    /// Connect with native client library, or REST API if `native` feature is disabled.
+    #[cfg(feature = "native")]
+    pub fn connect(&self) -> Result<Taos, Error> {
+        self.connect_native()
     }
 
-    fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
-        Ok(())
+    /// Connect with native client library, or REST API if `native` feature is disabled.
+    #[cfg(all(feature = "rest", not(feature = "native")))]
+    pub fn connect(&self) -> Result<Taos, Error> {
+        self.connect_rest()
     }
 
-    fn has_broken(&self, _: &mut Self::Connection) -> bool {
-        false
+    /// Connect with the backend selected at runtime.
+    ///
+    /// ```ignore
+    /// let taos = TaosCfgBuilder::default()
+    ///     .ip("localhost")
+    ///     .user("root")
+    ///     .pass("taosdata")
+    ///     .db("log")
+    ///     .port(6030u16)
+    ///     .backend(Backend::Rest)
+    ///     .build()?
+    ///     .connect_client()?;
+    /// let data = taos.query("show databases").await?;
+    /// ```
+    pub fn connect_client(&self) -> Result<Box<dyn TaosClient>, Error> {
+        match self.backend {
+            #[cfg(feature = "native")]
+            Backend::Native => Ok(Box::new(self.connect_native()?)),
+            #[cfg(feature = "rest")]
+            Backend::Rest => Ok(Box::new(self.connect_rest()?)),
+            #[allow(unreachable_patterns)]
+            backend => Err(Error::BackendUnavailable(backend)),
+        }
     }
 }
 
@@ -0,0 +1,314 @@
+//! Typed metadata of databases, super tables, tables and tags, see [TaosClient].
+use crate::*;
+
+use serde::{Deserialize, Deserializer};
+
+fn de_precision<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<TimestampPrecision, D::Error> {
+    let precision = String::deserialize(deserializer)?;
+    Ok(match precision.as_str() {
+        "ms" => TimestampPrecision::Milli,
+        "us" => TimestampPrecision::Micro,
+        "ns" => TimestampPrecision::Nano,
+        _ => TimestampPrecision::Unknown,
+    })
+}
+
+fn de_null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// A database, by `show databases`.
+///
+/// Columns differ between server versions, so all but name and created time are optional.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DatabaseInfo {
+    pub name: String,
+    pub created_time: Timestamp,
+    #[serde(default)]
+    pub ntables: u64,
+    #[serde(default)]
+    pub vgroups: u32,
+    #[serde(default)]
+    pub replica: u16,
+    #[serde(default)]
+    pub quorum: u16,
+    /// Days per data file.
+    #[serde(default)]
+    pub days: u32,
+    /// Days to keep, like `3650` or `3650,3650,3650` for different tiers.
+    #[serde(default, rename = "keep0,keep1,keep(D)", alias = "keep")]
+    pub keep: String,
+    #[serde(default, rename = "cache(MB)", alias = "cache")]
+    pub cache: u32,
+    #[serde(default)]
+    pub blocks: u32,
+    #[serde(default)]
+    pub minrows: u32,
+    #[serde(default)]
+    pub maxrows: u32,
+    #[serde(default, rename = "wallevel")]
+    pub wal_level: u8,
+    #[serde(default)]
+    pub fsync: u32,
+    #[serde(default)]
+    pub comp: u8,
+    #[serde(default, rename = "cachelast")]
+    pub cache_last: u8,
+    #[serde(default = "default_precision", deserialize_with = "de_precision")]
+    pub precision: TimestampPrecision,
+    #[serde(default)]
+    pub update: u8,
+    #[serde(default)]
+    pub status: String,
+}
+
+fn default_precision() -> TimestampPrecision {
+    TimestampPrecision::Milli
+}
+
+impl DatabaseInfo {
+    /// Days to keep of each tier.
+    pub fn keep_days(&self) -> Vec<u32> {
+        self.keep
+            .split(',')
+            .filter_map(|v| v.trim().parse().ok())
+            .collect()
+    }
+}
+
+/// A super table, by `show stables`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct StableInfo {
+    pub name: String,
+    pub created_time: Timestamp,
+    /// Number of columns.
+    #[serde(default)]
+    pub columns: u32,
+    /// Number of tags.
+    #[serde(default)]
+    pub tags: u32,
+    /// Number of child tables.
+    #[serde(default)]
+    pub tables: u64,
+}
+
+/// A table, by `show tables`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TableInfo {
+    #[serde(rename = "table_name")]
+    pub name: String,
+    pub created_time: Timestamp,
+    #[serde(default)]
+    pub columns: u32,
+    /// Super table of a child table, `None` for normal tables.
+    #[serde(default, deserialize_with = "de_stable_name")]
+    pub stable_name: Option<String>,
+    #[serde(default, deserialize_with = "de_null_as_default")]
+    pub uid: i64,
+    #[serde(default, deserialize_with = "de_null_as_default")]
+    pub tid: i32,
+    #[serde(default, rename = "vgId", deserialize_with = "de_null_as_default")]
+    pub vg_id: i32,
+}
+
+fn de_stable_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.filter(|name| !name.is_empty()))
+}
+
+/// Tag definition and value of a table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagValue {
+    pub name: String,
+    pub type_: TaosDataType,
+    pub bytes: i16,
+    pub value: Field,
+}
+
+pub(crate) fn parse_rows<T: serde::de::DeserializeOwned>(
+    data: TaosQueryData,
+) -> Result<Vec<T>, Error> {
+    Ok(data.deserialize().collect::<Result<_, _>>()?)
+}
+
+/// Tags of the first row of a tag query, `tags` are in the same order of the query.
+pub(crate) fn parse_tags(tags: Vec<ColumnMeta>, data: TaosQueryData) -> Vec<TagValue> {
+    let values = data.rows.into_iter().next().unwrap_or_default();
+    let mut values = values.into_iter();
+    tags.into_iter()
+        .map(|tag| TagValue {
+            name: tag.name,
+            type_: tag.type_,
+            bytes: tag.bytes,
+            value: values.next().unwrap_or(Field::Null),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn meta(name: &str, type_: TaosDataType) -> ColumnMeta {
+        ColumnMeta {
+            name: name.to_string(),
+            type_,
+            bytes: 0,
+        }
+    }
+
+    fn ts() -> Field {
+        Field::Timestamp(Timestamp::new(1626006833639, TimestampPrecision::Milli))
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test parsing `show databases` rows
+    fn parse_databases() -> Result<(), Error> {
+        let data = TaosQueryData {
+            column_meta: vec![
+                meta("name", TaosDataType::Binary),
+                meta("created_time", TaosDataType::Timestamp),
+                meta("ntables", TaosDataType::Int),
+                meta("replica", TaosDataType::SmallInt),
+                meta("keep0,keep1,keep(D)", TaosDataType::Binary),
+                meta("cache(MB)", TaosDataType::Int),
+                meta("precision", TaosDataType::Binary),
+                meta("status", TaosDataType::Binary),
+            ],
+            rows: vec![vec![
+                Field::Binary("db1".into()),
+                ts(),
+                Field::Int(3),
+                Field::SmallInt(1),
+                Field::Binary("3650,3650,3650".into()),
+                Field::Int(16),
+                Field::Binary("us".into()),
+                Field::Binary("ready".into()),
+            ]],
+        };
+        let dbs: Vec<DatabaseInfo> = parse_rows(data)?;
+        assert_eq!(dbs.len(), 1);
+        let db = &dbs[0];
+        assert_eq!(db.name, "db1");
+        assert_eq!(db.ntables, 3);
+        assert_eq!(db.replica, 1);
+        assert_eq!(db.keep_days(), vec![3650; 3]);
+        assert_eq!(db.cache, 16);
+        assert_eq!(db.precision, TimestampPrecision::Micro);
+        assert_eq!(db.status, "ready");
+        assert_eq!(db.blocks, 0);
+        Ok(())
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test parsing `show tables` rows and tag values
+    fn parse_tables_and_tags() -> Result<(), Error> {
+        let data = TaosQueryData {
+            column_meta: vec![
+                meta("table_name", TaosDataType::Binary),
+                meta("created_time", TaosDataType::Timestamp),
+                meta("columns", TaosDataType::SmallInt),
+                meta("stable_name", TaosDataType::Binary),
+                meta("uid", TaosDataType::BigInt),
+                meta("tid", TaosDataType::Int),
+                meta("vgId", TaosDataType::Int),
+            ],
+            rows: vec![
+                vec![
+                    Field::Binary("tb1".into()),
+                    ts(),
+                    Field::SmallInt(2),
+                    Field::Binary("st1".into()),
+                    Field::BigInt(1),
+                    Field::Int(1),
+                    Field::Int(3),
+                ],
+                vec![
+                    Field::Binary("tb2".into()),
+                    ts(),
+                    Field::SmallInt(2),
+                    Field::Null,
+                    Field::BigInt(2),
+                    Field::Int(2),
+                    Field::Int(3),
+                ],
+            ],
+        };
+        let tables: Vec<TableInfo> = parse_rows(data)?;
+        assert_eq!(tables[0].stable_name.as_deref(), Some("st1"));
+        assert_eq!(tables[0].vg_id, 3);
+        assert_eq!(tables[1].name, "tb2");
+        assert_eq!(tables[1].stable_name, None);
+
+        let tags = vec![
+            meta("location", TaosDataType::Binary),
+            meta("group_id", TaosDataType::Int),
+        ];
+        let data = TaosQueryData {
+            column_meta: tags.clone(),
+            rows: vec![vec![Field::Binary("beijing".into()), Field::Null]],
+        };
+        let tags = parse_tags(tags, data);
+        assert_eq!(tags[0].name, "location");
+        assert_eq!(tags[0].value, Field::Binary("beijing".into()));
+        assert_eq!(tags[1].value, Field::Null);
+        Ok(())
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test metadata of native connection
+    async fn metadata() -> Result<(), Error> {
+        let taos = crate::test::taos()?;
+        let db = "rs_metadata";
+        taos.exec(format!("drop database if exists {}", db)).await?;
+        taos.exec(format!("create database if not exists {} keep 36500", db))
+            .await?;
+        taos.exec(format!("use {}", db)).await?;
+        taos.exec("create stable st1 (ts timestamp, v int) tags (location binary(10), gid int)")
+            .await?;
+        taos.exec("create table tb1 using st1 tags('beijing', 1)")
+            .await?;
+        taos.exec("create table tb2 (ts timestamp, v int)").await?;
+
+        let taos: &dyn TaosClient = &taos;
+        let database = taos
+            .list_databases()
+            .await?
+            .into_iter()
+            .find(|database| database.name == db)
+            .unwrap();
+        assert_eq!(database.keep_days()[0], 36500);
+        assert_eq!(database.precision, TimestampPrecision::Milli);
+
+        let stables = taos.list_stables(db).await?;
+        assert_eq!(stables.len(), 1);
+        assert_eq!(stables[0].name, "st1");
+        assert_eq!(stables[0].tags, 2);
+
+        assert_eq!(taos.list_tables(db, None).await?.len(), 2);
+        let tables = taos.list_tables(db, Some("st1")).await?;
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].name, "tb1");
+        assert_eq!(taos.list_tables(db, Some("ST1")).await?.len(), 1);
+
+        let tags = taos.table_tags("tb1").await?;
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].value, Field::Binary("beijing".into()));
+        assert_eq!(tags[1].value, Field::Int(1));
+        assert!(taos.table_tags("tb2").await?.is_empty());
+        assert!(taos.table_tags("st1").await.is_err());
+        assert!(taos.table_tags(&format!("{}.st1", db)).await.is_err());
+
+        taos.exec(&format!("drop database {}", db)).await?;
+        Ok(())
+    }
+}
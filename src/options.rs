@@ -0,0 +1,293 @@
+//! Global options of the native client library, set by `taos_options`.
+//!
+//! Options take effect for the whole process, so they could be set only once, before the first
+//! connection established.
+//!
+//! ```ignore
+//! let options = ClientOptionsBuilder::default()
+//!     .charset("UTF-8")
+//!     .timezone("Asia/Shanghai")
+//!     .build()?;
+//! let taos = TaosCfgBuilder::default()
+//!     // ...
+//!     .options(options)
+//!     .build()?
+//!     .connect()?;
+//! ```
+use crate::*;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Valid range of shell activity timer in seconds.
+const SHELL_ACTIVITY_TIMER_RANGE: std::ops::RangeInclusive<u32> = 1..=120;
+
+/// Options of the client library, see [module documentation](crate::options).
+#[derive(Builder, Debug, Clone, Default, PartialEq, Eq)]
+#[builder(
+    default,
+    setter(into, strip_option),
+    build_fn(private, name = "build_unchecked")
+)]
+pub struct ClientOptions {
+    /// Locale, like `en_US.UTF-8`.
+    locale: Option<String>,
+    /// Charset, like `UTF-8`.
+    charset: Option<String>,
+    /// Timezone, like `UTC`, `UTC-8` or `Asia/Shanghai`.
+    timezone: Option<String>,
+    /// Directory of `taos.cfg`.
+    config_dir: Option<PathBuf>,
+    /// Heartbeat interval in seconds, in range `[1, 120]`.
+    shell_activity_timer: Option<u32>,
+}
+
+impl ClientOptionsBuilder {
+    /// Build and validate the options.
+    pub fn build(&self) -> Result<ClientOptions, Error> {
+        let options = self
+            .build_unchecked()
+            .map_err(|err| Error::InvalidOptions(err.to_string()))?;
+        options.validate()?;
+        Ok(options)
+    }
+}
+
+fn check_chars(name: &str, value: &str, valid: impl Fn(char) -> bool) -> Result<(), Error> {
+    if value.is_empty() || !value.chars().all(valid) {
+        return Err(Error::InvalidOptions(format!(
+            "invalid {}: {:?}",
+            name, value
+        )));
+    }
+    Ok(())
+}
+
+impl ClientOptions {
+    pub fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+
+    pub fn charset(&self) -> Option<&str> {
+        self.charset.as_deref()
+    }
+
+    pub fn timezone(&self) -> Option<&str> {
+        self.timezone.as_deref()
+    }
+
+    pub fn config_dir(&self) -> Option<&std::path::Path> {
+        self.config_dir.as_deref()
+    }
+
+    pub fn shell_activity_timer(&self) -> Option<u32> {
+        self.shell_activity_timer
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Check option values before passing to the client library.
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Some(locale) = &self.locale {
+            check_chars("locale", locale, |c| {
+                c.is_ascii_alphanumeric() || "._-@".contains(c)
+            })?;
+        }
+        if let Some(charset) = &self.charset {
+            check_chars("charset", charset, |c| {
+                c.is_ascii_alphanumeric() || "_-".contains(c)
+            })?;
+        }
+        if let Some(timezone) = &self.timezone {
+            check_chars("timezone", timezone, |c| {
+                c.is_ascii_alphanumeric() || "/_+-:".contains(c)
+            })?;
+        }
+        if let Some(dir) = &self.config_dir {
+            if !dir.is_dir() {
+                return Err(Error::InvalidOptions(format!(
+                    "config dir {} is not a directory",
+                    dir.display()
+                )));
+            }
+        }
+        if let Some(timer) = self.shell_activity_timer {
+            if !SHELL_ACTIVITY_TIMER_RANGE.contains(&timer) {
+                return Err(Error::InvalidOptions(format!(
+                    "shell activity timer {} out of range {:?}",
+                    timer, SHELL_ACTIVITY_TIMER_RANGE
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Options from DSN parameters `locale`, `charset`, `timezone`, `configDir` and
+    /// `shellActivityTimer`, `None` if no one is set.
+    pub fn from_params(params: &BTreeMap<String, String>) -> Result<Option<Self>, Error> {
+        let options = ClientOptions {
+            locale: params.get("locale").cloned(),
+            charset: params.get("charset").cloned(),
+            timezone: params.get("timezone").cloned(),
+            config_dir: params.get("configDir").map(PathBuf::from),
+            shell_activity_timer: params
+                .get("shellActivityTimer")
+                .map(|v| {
+                    v.parse().map_err(|_| {
+                        Error::InvalidOptions(format!("invalid shell activity timer: {:?}", v))
+                    })
+                })
+                .transpose()?,
+        };
+        if options.is_empty() {
+            return Ok(None);
+        }
+        options.validate()?;
+        Ok(Some(options))
+    }
+}
+
+#[cfg(feature = "native")]
+mod native {
+    use super::*;
+    use crate::bindings::*;
+
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    lazy_static::lazy_static! {
+        /// Options applied to the client library.
+        static ref APPLIED: Mutex<Option<ClientOptions>> = Mutex::new(None);
+    }
+
+    static CONNECTED: AtomicBool = AtomicBool::new(false);
+
+    /// Called before each connection, options could not be changed since then.
+    pub(crate) fn connecting() {
+        CONNECTED.store(true, Ordering::SeqCst);
+    }
+
+    fn set_option(option: TSDB_OPTION, name: &str, value: &str) -> Result<(), Error> {
+        let cstr = value.to_c_string();
+        let code = unsafe { taos_options(option, cstr.as_ptr() as _) };
+        if code != 0 {
+            return Err(Error::InvalidOptions(format!(
+                "failed to set {} to {:?}",
+                name, value
+            )));
+        }
+        Ok(())
+    }
+
+    impl ClientOptions {
+        /// Set options to the client library.
+        ///
+        /// It could be called only once before the first connection, applying the same options
+        /// again is a no-op.
+        pub fn apply(&self) -> Result<(), Error> {
+            let mut applied = APPLIED.lock().unwrap();
+            if let Some(applied) = applied.as_ref() {
+                if applied == self {
+                    return Ok(());
+                }
+                return Err(Error::InvalidOptions(
+                    "client options have been set to different values".to_string(),
+                ));
+            }
+            if CONNECTED.load(Ordering::SeqCst) {
+                return Err(Error::InvalidOptions(
+                    "client options should be set before the first connection".to_string(),
+                ));
+            }
+            self.validate()?;
+            // config dir should be set at first, since it reloads the config file.
+            if let Some(dir) = &self.config_dir {
+                set_option(
+                    TSDB_OPTION_TSDB_OPTION_CONFIGDIR,
+                    "config dir",
+                    &dir.to_string_lossy(),
+                )?;
+            }
+            if let Some(locale) = &self.locale {
+                set_option(TSDB_OPTION_TSDB_OPTION_LOCALE, "locale", locale)?;
+            }
+            if let Some(charset) = &self.charset {
+                set_option(TSDB_OPTION_TSDB_OPTION_CHARSET, "charset", charset)?;
+            }
+            if let Some(timezone) = &self.timezone {
+                set_option(TSDB_OPTION_TSDB_OPTION_TIMEZONE, "timezone", timezone)?;
+            }
+            if let Some(timer) = self.shell_activity_timer {
+                set_option(
+                    TSDB_OPTION_TSDB_OPTION_SHELL_ACTIVITY_TIMER,
+                    "shell activity timer",
+                    &timer.to_string(),
+                )?;
+            }
+            *applied = Some(self.clone());
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+pub(crate) use native::connecting;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test client options validation
+    fn validate_options() -> Result<(), Error> {
+        let options = ClientOptionsBuilder::default()
+            .locale("en_US.UTF-8")
+            .charset("UTF-8")
+            .timezone("Asia/Shanghai")
+            .config_dir(std::env::temp_dir())
+            .shell_activity_timer(3u32)
+            .build()?;
+        assert_eq!(options.charset(), Some("UTF-8"));
+        assert!(ClientOptionsBuilder::default().build()?.is_empty());
+
+        let invalid = [
+            ClientOptionsBuilder::default().charset("").build(),
+            ClientOptionsBuilder::default().charset("UTF-8\0").build(),
+            ClientOptionsBuilder::default().timezone("UTC 8").build(),
+            ClientOptionsBuilder::default()
+                .config_dir("/path/not/exists")
+                .build(),
+            ClientOptionsBuilder::default()
+                .shell_activity_timer(0u32)
+                .build(),
+            ClientOptionsBuilder::default()
+                .shell_activity_timer(121u32)
+                .build(),
+        ];
+        for options in invalid {
+            assert!(matches!(options, Err(Error::InvalidOptions(_))));
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test client options from DSN parameters
+    fn options_from_dsn() -> Result<(), Error> {
+        let cfg = TaosCfg::from_dsn("taos://localhost/db?timezone=UTC&charset=UTF-8")?;
+        let options = ClientOptions::from_params(cfg.params())?.unwrap();
+        assert_eq!(options.timezone(), Some("UTC"));
+        assert_eq!(options.charset(), Some("UTF-8"));
+        assert_eq!(options.locale(), None);
+
+        let cfg = TaosCfg::from_dsn("taos://localhost/db?other=1")?;
+        assert_eq!(ClientOptions::from_params(cfg.params())?, None);
+
+        let cfg = TaosCfg::from_dsn("taos://localhost/db?shellActivityTimer=abc")?;
+        assert!(ClientOptions::from_params(cfg.params()).is_err());
+        Ok(())
+    }
+}
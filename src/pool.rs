@@ -0,0 +1,322 @@
+//! Connection pools by [r2d2] with feature `r2d2`, or [bb8] with feature `bb8`.
+//!
+//! Connections are validated on checkout with a cheap probe query by default, and connections
+//! seen fatal errors are dropped instead of returning to the pool.
+//!
+//! r2d2 validates connections synchronously, so [TaosManager] only supports native connections.
+//! [TaosPool] of REST connections skips validation, as the HTTP connections are managed by the
+//! http client. Use bb8 to pool REST connections with validation.
+//!
+//! ```ignore
+//! use libtaos::pool::*;
+//! let manager = TaosManager::new(cfg).validation(ValidationStrategy::Ping);
+//! let pool = r2d2::Pool::builder().max_size(8).build(manager)?;
+//! let taos = pool.get()?;
+//! ```
+//!
+//! [r2d2]: https://crates.io/crates/r2d2
+//! [bb8]: https://crates.io/crates/bb8
+use crate::*;
+
+#[cfg(feature = "bb8")]
+mod bb8;
+#[cfg(feature = "bb8")]
+pub use self::bb8::*;
+
+/// Connection pool with default validation strategy.
+#[cfg(feature = "r2d2")]
+pub type TaosPool = r2d2::Pool<TaosCfg>;
+
+/// Probe SQL of [ValidationStrategy::Ping].
+pub const PING_SQL: &str = "select server_version()";
+
+/// How to check a connection before it's handed out by the pool.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ValidationStrategy {
+    /// Only check if the connection is broken, no query issued.
+    Skip,
+    /// Probe with `select server_version()`.
+    #[default]
+    Ping,
+    /// Probe with custom SQL.
+    Query(String),
+}
+
+/// Connections that could be checked by the pool.
+pub trait Validate {
+    /// Execute `sql` synchronously and discard the result.
+    fn probe(&self, sql: &str) -> Result<(), Error>;
+
+    /// Whether a fatal error has been seen on the connection.
+    fn is_broken(&self) -> bool;
+}
+
+/// Creates connections for [TaosManager].
+pub trait Connector: Send + Sync + 'static {
+    type Connection: Send + 'static;
+
+    fn connect(&self) -> Result<Self::Connection, Error>;
+}
+
+#[cfg(feature = "native")]
+impl Validate for crate::client::Taos {
+    fn probe(&self, sql: &str) -> Result<(), Error> {
+        self.raw_query(sql).map(|_| ())
+    }
+
+    fn is_broken(&self) -> bool {
+        crate::client::Taos::is_broken(self)
+    }
+}
+
+impl Connector for TaosCfg {
+    type Connection = Taos;
+
+    fn connect(&self) -> Result<Self::Connection, Error> {
+        TaosCfg::connect(self)
+    }
+}
+
+#[cfg(feature = "r2d2")]
+fn validate<C: Validate>(conn: &C, validation: &ValidationStrategy) -> Result<(), Error> {
+    if conn.is_broken() {
+        return Err(Error::ConnectionInvalid);
+    }
+    match validation {
+        ValidationStrategy::Skip => Ok(()),
+        ValidationStrategy::Ping => conn.probe(PING_SQL),
+        ValidationStrategy::Query(sql) => conn.probe(sql),
+    }
+}
+
+/// Connection manager with configurable validation strategy.
+#[derive(Debug, Clone)]
+pub struct TaosManager<C = TaosCfg> {
+    connector: C,
+    validation: ValidationStrategy,
+}
+
+impl<C: Connector> TaosManager<C> {
+    pub fn new(connector: C) -> Self {
+        Self {
+            connector,
+            validation: ValidationStrategy::default(),
+        }
+    }
+
+    /// Set validation strategy, default is [ValidationStrategy::Ping].
+    pub fn validation(mut self, validation: ValidationStrategy) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    pub fn connector(&self) -> &C {
+        &self.connector
+    }
+}
+
+#[cfg(feature = "r2d2")]
+impl<C: Connector> r2d2::ManageConnection for TaosManager<C>
+where
+    C::Connection: Validate,
+{
+    type Connection = C::Connection;
+    type Error = Error;
+
+    fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        self.connector.connect()
+    }
+
+    fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        validate(conn, &self.validation)
+    }
+
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        conn.is_broken()
+    }
+}
+
+#[cfg(all(feature = "r2d2", feature = "native"))]
+impl r2d2::ManageConnection for TaosCfg {
+    type Connection = Taos;
+    type Error = Error;
+
+    fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        TaosCfg::connect(self)
+    }
+
+    fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        validate(conn, &ValidationStrategy::default())
+    }
+
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        conn.is_broken()
+    }
+}
+
+#[cfg(all(feature = "r2d2", not(feature = "native")))]
+impl r2d2::ManageConnection for TaosCfg {
+    type Connection = Taos;
+    type Error = Error;
+
+    fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        TaosCfg::connect(self)
+    }
+
+    /// Same as [ValidationStrategy::Skip], REST connections could not be probed synchronously.
+    fn is_valid(&self, _: &mut Self::Connection) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        conn.is_broken()
+    }
+}
+
+#[cfg(all(test, feature = "r2d2"))]
+mod test {
+    use super::*;
+
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// A fake server shared by connections.
+    #[derive(Debug, Default)]
+    struct FakeServer {
+        down: AtomicBool,
+        connects: AtomicUsize,
+        probes: Mutex<Vec<String>>,
+    }
+
+    #[derive(Debug)]
+    struct FakeConnection {
+        server: Arc<FakeServer>,
+        broken: AtomicBool,
+    }
+
+    impl FakeConnection {
+        fn exec(&self, _sql: &str) -> Result<(), Error> {
+            if self.server.down.load(Ordering::SeqCst) {
+                let err = TaosError {
+                    code: TaosCode::RpcNetworkUnavail,
+                    err: "Unable to establish connection".into(),
+                };
+                if err.is_fatal() {
+                    self.broken.store(true, Ordering::SeqCst);
+                }
+                return Err(err.into());
+            }
+            Ok(())
+        }
+    }
+
+    impl Validate for FakeConnection {
+        fn probe(&self, sql: &str) -> Result<(), Error> {
+            self.server.probes.lock().unwrap().push(sql.to_string());
+            self.exec(sql)
+        }
+
+        fn is_broken(&self) -> bool {
+            self.broken.load(Ordering::SeqCst)
+        }
+    }
+
+    #[derive(Debug, Default, Clone)]
+    struct FakeConnector(Arc<FakeServer>);
+
+    impl Connector for FakeConnector {
+        type Connection = FakeConnection;
+
+        fn connect(&self) -> Result<Self::Connection, Error> {
+            if self.0.down.load(Ordering::SeqCst) {
+                return Err(Error::ConnectionInvalid);
+            }
+            self.0.connects.fetch_add(1, Ordering::SeqCst);
+            Ok(FakeConnection {
+                server: self.0.clone(),
+                broken: AtomicBool::new(false),
+            })
+        }
+    }
+
+    fn fake_pool(manager: TaosManager<FakeConnector>) -> r2d2::Pool<TaosManager<FakeConnector>> {
+        r2d2::Pool::builder()
+            .max_size(2)
+            .min_idle(Some(0))
+            .connection_timeout(Duration::from_millis(200))
+            .build(manager)
+            .unwrap()
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test connections are probed on checkout
+    fn pool_probe() {
+        let connector = FakeConnector::default();
+        let server = connector.0.clone();
+        let pool = fake_pool(TaosManager::new(connector));
+        {
+            let conn = pool.get().unwrap();
+            assert!(conn.exec("show databases").is_ok());
+        }
+        let _conn = pool.get().unwrap();
+        assert_eq!(server.connects.load(Ordering::SeqCst), 1);
+        assert_eq!(*server.probes.lock().unwrap(), vec![PING_SQL; 2]);
+
+        let connector = FakeConnector::default();
+        let server = connector.0.clone();
+        let manager = TaosManager::new(connector)
+            .validation(ValidationStrategy::Query("select 1".to_string()));
+        let _conn = fake_pool(manager).get().unwrap();
+        assert_eq!(*server.probes.lock().unwrap(), vec!["select 1"]);
+
+        let connector = FakeConnector::default();
+        let server = connector.0.clone();
+        let _conn = fake_pool(TaosManager::new(connector).validation(ValidationStrategy::Skip))
+            .get()
+            .unwrap();
+        assert!(server.probes.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test broken connections are dropped and reconnected
+    fn pool_broken() {
+        let connector = FakeConnector::default();
+        let server = connector.0.clone();
+        let pool = fake_pool(TaosManager::new(connector).validation(ValidationStrategy::Skip));
+        {
+            let conn = pool.get().unwrap();
+            server.down.store(true, Ordering::SeqCst);
+            assert!(conn.exec("show databases").unwrap_err().is_fatal());
+        }
+        // the broken connection is not returned to the pool
+        assert_eq!(pool.state().connections, 0);
+        assert!(pool.get().is_err());
+
+        server.down.store(false, Ordering::SeqCst);
+        let conn = pool.get().unwrap();
+        assert!(conn.exec("show databases").is_ok());
+        // r2d2 may also reconnect in background after the failed checkout.
+        assert!(server.connects.load(Ordering::SeqCst) >= 2);
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test idle connections failing the probe are not handed out
+    fn pool_server_down() {
+        let connector = FakeConnector::default();
+        let server = connector.0.clone();
+        let pool = fake_pool(TaosManager::new(connector));
+        drop(pool.get().unwrap());
+        assert_eq!(pool.state().idle_connections, 1);
+
+        server.down.store(true, Ordering::SeqCst);
+        assert!(pool.get().is_err());
+        assert_eq!(pool.state().connections, 0);
+
+        server.down.store(false, Ordering::SeqCst);
+        assert!(pool.get().is_ok());
+    }
+}
@@ -0,0 +1,90 @@
+use super::*;
+
+use async_trait::async_trait;
+
+/// Async connection pool by [bb8](https://crates.io/crates/bb8).
+///
+/// ```ignore
+/// let pool = AsyncTaosPool::builder()
+///     .max_size(8)
+///     .build(TaosManager::new(cfg))
+///     .await?;
+/// let taos = pool.get().await?;
+/// ```
+pub type AsyncTaosPool = ::bb8::Pool<TaosManager>;
+
+/// Checked out connection of [AsyncTaosPool].
+pub type AsyncPooledTaos<'a> = ::bb8::PooledConnection<'a, TaosManager>;
+
+#[async_trait]
+impl ::bb8::ManageConnection for TaosManager<TaosCfg> {
+    type Connection = Taos;
+    type Error = Error;
+
+    /// Connect in a blocking thread, `taos_connect` and the version check block until the server
+    /// responds.
+    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        let cfg = self.connector.clone();
+        match tokio::task::spawn_blocking(move || cfg.connect()).await {
+            Ok(res) => res,
+            Err(err) => std::panic::resume_unwind(err.into_panic()),
+        }
+    }
+
+    /// Reset current database to [TaosCfg]'s `db` and probe the connection.
+    async fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        if conn.is_broken() {
+            return Err(Error::ConnectionInvalid);
+        }
+        if let Some(db) = &self.connector.db {
+            conn.use_database(db).await?;
+            // `use` is a round trip to server, no need to ping again.
+            if self.validation == ValidationStrategy::Ping {
+                return Ok(());
+            }
+        }
+        match &self.validation {
+            ValidationStrategy::Skip => Ok(()),
+            ValidationStrategy::Ping => conn.exec(PING_SQL).await,
+            ValidationStrategy::Query(sql) => conn.exec(sql.as_str()).await,
+        }
+    }
+
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        conn.is_broken()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::cfg;
+    use proc_test_catalog::test_catalogue;
+
+    #[tokio::test]
+    #[test_catalogue]
+    /// Test current database is reset on recycle
+    async fn bb8_recycle() -> Result<(), Box<dyn std::error::Error>> {
+        let cfg = cfg();
+        let default_db = cfg.db.clone().unwrap();
+        let db = "rs_bb8_recycle";
+        let pool = AsyncTaosPool::builder()
+            .max_size(1)
+            .build(TaosManager::new(cfg))
+            .await?;
+        {
+            let taos = pool.get().await?;
+            taos.exec(&format!("create database if not exists {}", db))
+                .await?;
+            taos.exec(&format!("use {}", db)).await?;
+        }
+        {
+            let taos = pool.get().await?;
+            let data = taos.query("select database()").await?;
+            assert_eq!(data.rows[0][0], Field::Binary(default_db.into()));
+            taos.exec(&format!("drop database {}", db)).await?;
+        }
+        assert_eq!(pool.state().connections, 1);
+        Ok(())
+    }
+}
@@ -9,12 +9,17 @@ use serde_json::Value;
 use crate::field::*;
 use crate::*;
 use crate::{error::TaosCode, Error, TaosError};
+
+#[cfg(feature = "schemaless")]
+mod schemaless;
+
 #[derive(Debug, Clone)]
 pub struct Taos {
     client: reqwest::Client,
     endpoint: String,
     username: String,
     password: String,
+    database: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -176,12 +181,33 @@ impl Taos {
             endpoint,
             username,
             password,
+            database: None,
         }
     }
+
+    /// Set database of the connection, used by APIs out of SQL such as schemaless insert.
+    pub fn with_database(mut self, database: impl Into<String>) -> Self {
+        self.database = Some(database.into());
+        self
+    }
+
+    /// Connections of REST API are managed by the http client, it's never broken.
+    pub fn is_broken(&self) -> bool {
+        false
+    }
+
+    pub fn database(&self) -> Option<&str> {
+        self.database.as_deref()
+    }
+    /// Create table, the name is quoted by [sql::ident], `options` is raw SQL such as column definitions.
     pub async fn create_table(&self, table: &str, options: Option<&str>) -> Result<(), Error> {
-        self.query(&format!("create table {} {}", table, options.unwrap_or("")))
-            .await
-            .map(|_| ())
+        self.query(&format!(
+            "create table {} {}",
+            sql::ident(table)?,
+            options.unwrap_or("")
+        ))
+        .await
+        .map(|_| ())
     }
 
     pub async fn create_table_if_not_exists(
@@ -191,7 +217,7 @@ impl Taos {
     ) -> Result<(), Error> {
         self.query(&format!(
             "create table if not exists {} {}",
-            table,
+            sql::ident(table)?,
             options.unwrap_or("")
         ))
         .await
@@ -199,20 +225,26 @@ impl Taos {
     }
 
     pub async fn create_database(&self, database: &str) -> Result<(), Error> {
-        self.exec(&format!("create database {}", database)).await
+        self.exec(&format!("create database {}", sql::ident(database)?))
+            .await
     }
 
     pub async fn create_database_if_exists(&self, database: &str) -> Result<(), Error> {
-        self.exec(&format!("create database if not exists {}", database))
-            .await
+        self.exec(&format!(
+            "create database if not exists {}",
+            sql::ident(database)?
+        ))
+        .await
     }
 
     pub async fn use_database(&self, database: &str) -> Result<(), Error> {
-        self.query(&format!("use {}", database)).await.map(|_| ())
+        self.query(&format!("use {}", sql::ident(database)?))
+            .await
+            .map(|_| ())
     }
 
     pub async fn describe(&self, table: &str) -> Result<TaosDescribe, Error> {
-        self.query(&format!("describe {}", table))
+        self.query(&format!("describe {}", sql::ident(table)?))
             .await
             .map(|res| TaosDescribe::from(res))
     }
@@ -255,4 +287,14 @@ impl Taos {
         let res: TaosQueryResponse = res.json().await?;
         res.into()
     }
+
+    /// Query and deserialize each row into `T`, see [crate::de] for details.
+    pub async fn query_as<T: serde::de::DeserializeOwned>(
+        &self,
+        sql: &str,
+    ) -> Result<Vec<T>, Error> {
+        let data = self.query(sql).await?;
+        let rows = data.deserialize().collect::<Result<_, _>>()?;
+        Ok(rows)
+    }
 }
@@ -0,0 +1,317 @@
+//! Schemaless insert by taosAdapter's InfluxDB and OpenTSDB endpoints.
+use super::*;
+use crate::schemaless::*;
+
+use reqwest::{StatusCode, Url};
+
+/// Error response of taosAdapter, `message` for InfluxDB and OpenTSDB, `desc` for SQL.
+#[derive(Debug, Deserialize)]
+struct AdapterError {
+    code: i32,
+    #[serde(alias = "desc")]
+    message: String,
+}
+
+/// Value of `precision` parameter of InfluxDB write API.
+fn influxdb_precision(precision: TSDB_SML_TIMESTAMP_TYPE) -> Option<&'static str> {
+    match precision {
+        TSDB_SML_TIMESTAMP_TYPE::NonConfigured => None,
+        TSDB_SML_TIMESTAMP_TYPE::Hours => Some("h"),
+        TSDB_SML_TIMESTAMP_TYPE::Minutes => Some("m"),
+        TSDB_SML_TIMESTAMP_TYPE::Seconds => Some("s"),
+        TSDB_SML_TIMESTAMP_TYPE::Milliseconds => Some("ms"),
+        TSDB_SML_TIMESTAMP_TYPE::Microseconds => Some("u"),
+        TSDB_SML_TIMESTAMP_TYPE::Nanoseconds => Some("ns"),
+    }
+}
+
+/// JSON lines as one JSON array, since the endpoint accepts a single JSON document, with the
+/// number of points.
+fn json_body(lines: &[impl AsRef<str>]) -> Result<(String, usize), Error> {
+    let mut points = Vec::new();
+    for line in lines {
+        match serde_json::from_str(line.as_ref()) {
+            Ok(Value::Array(values)) => points.extend(values),
+            Ok(value) => points.push(value),
+            Err(err) => {
+                return Err(Error::InvalidSchemaless(format!(
+                    "invalid json line: {}",
+                    err
+                )))
+            }
+        }
+    }
+    let len = points.len();
+    Ok((Value::Array(points).to_string(), len))
+}
+
+impl Taos {
+    fn adapter_url(&self, path: &str) -> Result<Url, Error> {
+        Url::parse(&self.endpoint)
+            .and_then(|url| url.join(path))
+            .map_err(|err| Error::InvalidDsn(format!("{}: {}", self.endpoint, err)))
+    }
+
+    /// Schemaless insert into the connection's database by taosAdapter.
+    ///
+    /// - InfluxDB line protocol is written to `/influxdb/v1/write`, with `precision`.
+    /// - OpenTSDB telnet and JSON protocols are written to `/opentsdb/v1/put/telnet/<db>` and
+    ///   `/opentsdb/v1/put/json/<db>`, timestamps in seconds and milliseconds are told apart by
+    ///   server, so only these `precision` are accepted.
+    ///
+    /// taosAdapter does not report affected rows, number of points is returned on success.
+    pub async fn schemaless_insert(
+        &self,
+        lines: &[impl AsRef<str>],
+        protocol: TSDB_SML_PROTOCOL_TYPE,
+        precision: TSDB_SML_TIMESTAMP_TYPE,
+    ) -> Result<i32, Error> {
+        let db = self.database.as_deref().ok_or_else(|| {
+            Error::InvalidSchemaless("database is required by REST schemaless insert".to_string())
+        })?;
+        let join_lines = || lines.iter().map(|line| line.as_ref()).join("\n");
+        if matches!(
+            protocol,
+            TSDB_SML_PROTOCOL_TYPE::Telnet | TSDB_SML_PROTOCOL_TYPE::Json
+        ) && !matches!(
+            precision,
+            TSDB_SML_TIMESTAMP_TYPE::NonConfigured
+                | TSDB_SML_TIMESTAMP_TYPE::Seconds
+                | TSDB_SML_TIMESTAMP_TYPE::Milliseconds
+        ) {
+            return Err(Error::InvalidSchemaless(format!(
+                "precision {:?} is not supported by OpenTSDB protocol",
+                precision
+            )));
+        }
+        let (url, body, points) = match protocol {
+            TSDB_SML_PROTOCOL_TYPE::Line => {
+                let mut url = self.adapter_url("/influxdb/v1/write")?;
+                url.query_pairs_mut().append_pair("db", db);
+                if let Some(precision) = influxdb_precision(precision) {
+                    url.query_pairs_mut().append_pair("precision", precision);
+                }
+                (url, join_lines(), lines.len())
+            }
+            TSDB_SML_PROTOCOL_TYPE::Telnet => (
+                self.adapter_url(&format!("/opentsdb/v1/put/telnet/{}", db))?,
+                join_lines(),
+                lines.len(),
+            ),
+            TSDB_SML_PROTOCOL_TYPE::Json => {
+                let (body, points) = json_body(lines)?;
+                let url = self.adapter_url(&format!("/opentsdb/v1/put/json/{}", db))?;
+                (url, body, points)
+            }
+            TSDB_SML_PROTOCOL_TYPE::Unknown => {
+                return Err(Error::InvalidSchemaless("unknown protocol".to_string()))
+            }
+        };
+        debug!("schemaless insert {} lines to {}", lines.len(), url);
+        let res = self
+            .client
+            .post(url)
+            .basic_auth(&self.username, Some(&self.password))
+            .body(body)
+            .send()
+            .await?;
+        let status = res.status();
+        if status.is_success() {
+            return Ok(points as _);
+        }
+        let text = res.text().await?;
+        let err = match serde_json::from_str::<AdapterError>(&text) {
+            Ok(err) => TaosError {
+                code: err.code.into(),
+                err: err.message.into(),
+            },
+            // not from taosAdapter, e.g. a proxy in front of it is down, it's fatal so that the
+            // batch is not retried line by line.
+            Err(_) => TaosError {
+                code: if status == StatusCode::UNAUTHORIZED {
+                    TaosCode::RpcAuthFailure
+                } else {
+                    TaosCode::RpcNetworkUnavail
+                },
+                err: format!("{}: {}", status, text).into(),
+            },
+        };
+        Err(err.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use httpmock::prelude::*;
+
+    fn taos(server: &MockServer) -> Taos {
+        Taos::new(server.url("/rest/sql"), "root".into(), "taosdata".into()).with_database("test")
+    }
+
+    #[tokio::test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test REST schemaless insert against mocked taosAdapter
+    async fn rest_schemaless_insert() -> Result<(), Error> {
+        let server = MockServer::start_async().await;
+        let taos = taos(&server);
+
+        let line = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/influxdb/v1/write")
+                    .query_param("db", "test")
+                    .query_param("precision", "ms")
+                    .body("st,t1=a v=1i32 1626006833639\nst,t1=a v=2i32 1626006833640");
+                then.status(204);
+            })
+            .await;
+        let lines = [
+            "st,t1=a v=1i32 1626006833639",
+            "st,t1=a v=2i32 1626006833640",
+        ];
+        let res = taos
+            .schemaless_insert(
+                &lines,
+                TSDB_SML_LINE_PROTOCOL,
+                TSDB_SML_TIMESTAMP_MILLISECONDS,
+            )
+            .await?;
+        assert_eq!(res, 2);
+        line.assert_async().await;
+
+        let telnet = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/opentsdb/v1/put/telnet/test")
+                    .body("sys.cpu 1479496100 1 host=web01");
+                then.status(204);
+            })
+            .await;
+        taos.schemaless_insert(
+            &["sys.cpu 1479496100 1 host=web01"],
+            TSDB_SML_TELNET_PROTOCOL,
+            TSDB_SML_TIMESTAMP_SECONDS,
+        )
+        .await?;
+        telnet.assert_async().await;
+
+        let json = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/opentsdb/v1/put/json/test")
+                    .json_body(serde_json::json!([
+                        {"metric": "st", "timestamp": 1, "value": 1, "tags": {"t1": 1}},
+                        {"metric": "st", "timestamp": 2, "value": 2, "tags": {"t1": 1}},
+                        {"metric": "st", "timestamp": 3, "value": 3, "tags": {"t1": 1}},
+                    ]));
+                then.status(204);
+            })
+            .await;
+        let lines = [
+            r#"{"metric": "st", "timestamp": 1, "value": 1, "tags": {"t1": 1}}"#,
+            r#"[{"metric": "st", "timestamp": 2, "value": 2, "tags": {"t1": 1}},
+                {"metric": "st", "timestamp": 3, "value": 3, "tags": {"t1": 1}}]"#,
+        ];
+        let res = taos
+            .schemaless_insert(&lines, TSDB_SML_JSON_PROTOCOL, TSDB_SML_TIMESTAMP_SECONDS)
+            .await?;
+        assert_eq!(res, 3);
+        json.assert_async().await;
+
+        assert!(matches!(
+            taos.schemaless_insert(
+                &lines,
+                TSDB_SML_JSON_PROTOCOL,
+                TSDB_SML_TIMESTAMP_NANOSECONDS
+            )
+            .await,
+            Err(Error::InvalidSchemaless(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test REST schemaless insert errors and bisecting
+    async fn rest_schemaless_error() -> Result<(), Error> {
+        let server = MockServer::start_async().await;
+        let taos = taos(&server);
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/influxdb/v1/write")
+                    .body_contains("bad");
+                then.status(500)
+                    .json_body(serde_json::json!({"code": 0x0203, "message": "invalid value"}));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST).path("/influxdb/v1/write");
+                then.status(204);
+            })
+            .await;
+
+        let err = taos
+            .schemaless_insert(
+                &["bad"],
+                TSDB_SML_LINE_PROTOCOL,
+                TSDB_SML_TIMESTAMP_NOT_CONFIGURED,
+            )
+            .await;
+        match err {
+            Err(Error::RawTaosError(err)) => {
+                assert_eq!(err.code, TaosCode::TscInvalidValue);
+                assert_eq!(err.err, "invalid value");
+            }
+            err => panic!("unexpected result {:?}", err),
+        }
+
+        let client: &dyn TaosClient = &taos;
+        let lines = ["st v=1i32", "bad", "st v=3i32"];
+        let result = client
+            .schemaless_insert_bisect(
+                &lines,
+                TSDB_SML_LINE_PROTOCOL,
+                TSDB_SML_TIMESTAMP_NOT_CONFIGURED,
+            )
+            .await?;
+        assert_eq!(result.affected_rows, 2);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].index, 1);
+
+        let gateway = server
+            .mock_async(|when, then| {
+                when.method(POST).path("/opentsdb/v1/put/telnet/test");
+                then.status(502).body("<html>Bad Gateway</html>");
+            })
+            .await;
+        let lines = ["sys.cpu 1479496100 1 host=web01", "bad"];
+        match client
+            .schemaless_insert_bisect(
+                &lines,
+                TSDB_SML_TELNET_PROTOCOL,
+                TSDB_SML_TIMESTAMP_NOT_CONFIGURED,
+            )
+            .await
+        {
+            Err(err) => assert!(err.is_fatal()),
+            res => panic!("unexpected result {:?}", res),
+        }
+        // the batch is not bisected once the gateway is down.
+        gateway.assert_hits_async(1).await;
+
+        let taos = Taos::new(server.url("/rest/sql"), "root".into(), "taosdata".into());
+        assert!(matches!(
+            taos.schemaless_insert(
+                &["st v=1i32"],
+                TSDB_SML_LINE_PROTOCOL,
+                TSDB_SML_TIMESTAMP_NOT_CONFIGURED
+            )
+            .await,
+            Err(Error::InvalidSchemaless(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,447 @@
+//! Declarative super table schema, diffed against the live [TaosDescribe] to DDL statements.
+//!
+//! ```ignore
+//! let schema = StableSchema::new(
+//!     "meters",
+//!     vec![
+//!         ColumnMeta::new("ts", TaosDataType::Timestamp, 8),
+//!         ColumnMeta::new("current", TaosDataType::Float, 4),
+//!     ],
+//!     vec![ColumnMeta::new("location", TaosDataType::Binary, 64)],
+//! );
+//! // columns and tags not declared are kept unless drops are allowed.
+//! let schema = schema.allow_drop(true);
+//! // dry run, only returns the statements.
+//! let statements = schema.plan(&taos).await?;
+//! // execute the statements.
+//! schema.migrate(&taos).await?;
+//! ```
+use crate::*;
+
+/// Declared schema of a super table.
+#[derive(Debug, Clone)]
+pub struct StableSchema {
+    pub name: String,
+    /// Columns, the first one must be a timestamp.
+    pub columns: Vec<ColumnMeta>,
+    pub tags: Vec<ColumnMeta>,
+    /// Drop live columns and tags not declared, default is `false` to keep them.
+    pub allow_drop: bool,
+}
+
+/// A change from the live super table to the declared schema.
+#[derive(Debug, Clone)]
+pub enum SchemaChange {
+    /// The super table does not exist.
+    Create,
+    AddColumn(ColumnMeta),
+    /// Enlarge length of a binary or nchar column.
+    ModifyColumn(ColumnMeta),
+    DropColumn(String),
+    AddTag(ColumnMeta),
+    /// Enlarge length of a binary or nchar tag.
+    ModifyTag(ColumnMeta),
+    DropTag(String),
+}
+
+fn is_var_type(type_: TaosDataType) -> bool {
+    matches!(type_, TaosDataType::Binary | TaosDataType::NChar)
+}
+
+fn type_sql(meta: &ColumnMeta) -> Result<String, Error> {
+    use TaosDataType::*;
+    let sql = match meta.type_ {
+        Bool => "bool",
+        TinyInt => "tinyint",
+        SmallInt => "smallint",
+        Int => "int",
+        BigInt => "bigint",
+        UTinyInt => "tinyint unsigned",
+        USmallInt => "smallint unsigned",
+        UInt => "int unsigned",
+        UBigInt => "bigint unsigned",
+        Float => "float",
+        Double => "double",
+        Timestamp => "timestamp",
+        Json => "json",
+        Binary => return Ok(format!("binary({})", meta.bytes)),
+        NChar => return Ok(format!("nchar({})", meta.bytes)),
+        Null | Unknown => {
+            return Err(Error::InvalidSchema(format!(
+                "unsupported type of {}",
+                meta.name
+            )))
+        }
+    };
+    Ok(sql.to_string())
+}
+
+fn field_sql(meta: &ColumnMeta) -> Result<String, Error> {
+    Ok(format!("{} {}", sql::ident(&meta.name)?, type_sql(meta)?))
+}
+
+fn find<'a>(fields: &'a [ColumnMeta], name: &str) -> Option<&'a ColumnMeta> {
+    fields.iter().find(|f| f.name.eq_ignore_ascii_case(name))
+}
+
+/// Changes of columns or tags, as `(add, modify, drop)`.
+#[allow(clippy::type_complexity)]
+fn diff_fields(
+    kind: &str,
+    declared: &[ColumnMeta],
+    live: &[ColumnMeta],
+) -> Result<(Vec<ColumnMeta>, Vec<ColumnMeta>, Vec<String>), Error> {
+    let (mut add, mut modify) = (Vec::new(), Vec::new());
+    for field in declared {
+        let current = match find(live, &field.name) {
+            Some(current) => current,
+            None => {
+                add.push(field.clone());
+                continue;
+            }
+        };
+        if current.type_ != field.type_ {
+            return Err(Error::IncompatibleSchema(format!(
+                "type of {} {} changed from {:?} to {:?}",
+                kind, field.name, current.type_, field.type_
+            )));
+        }
+        if is_var_type(field.type_) && current.bytes != field.bytes {
+            if field.bytes < current.bytes {
+                return Err(Error::IncompatibleSchema(format!(
+                    "length of {} {} could not be shrunk from {} to {}",
+                    kind, field.name, current.bytes, field.bytes
+                )));
+            }
+            modify.push(field.clone());
+        }
+    }
+    let drop = live
+        .iter()
+        .filter(|f| find(declared, &f.name).is_none())
+        .map(|f| f.name.clone())
+        .collect();
+    Ok((add, modify, drop))
+}
+
+impl StableSchema {
+    pub fn new(name: impl Into<String>, columns: Vec<ColumnMeta>, tags: Vec<ColumnMeta>) -> Self {
+        Self {
+            name: name.into(),
+            columns,
+            tags,
+            allow_drop: false,
+        }
+    }
+
+    /// Whether to drop live columns and tags not declared, which deletes their data.
+    pub fn allow_drop(mut self, allow_drop: bool) -> Self {
+        self.allow_drop = allow_drop;
+        self
+    }
+
+    /// Check the schema could be created.
+    pub fn validate(&self) -> Result<(), Error> {
+        sql::ident(&self.name)?;
+        let invalid = |msg: String| Err(Error::InvalidSchema(format!("{}: {}", self.name, msg)));
+        match self.columns.first() {
+            Some(first) if first.type_ == TaosDataType::Timestamp => (),
+            _ => return invalid("the first column must be a timestamp".to_string()),
+        }
+        if self.tags.is_empty() {
+            return invalid("at least one tag is required".to_string());
+        }
+        let fields: Vec<_> = self.columns.iter().chain(self.tags.iter()).collect();
+        for (i, field) in fields.iter().enumerate() {
+            sql::ident(&field.name)?;
+            type_sql(field)?;
+            if is_var_type(field.type_) && field.bytes <= 0 {
+                return invalid(format!("invalid length of {}", field.name));
+            }
+            if fields[..i]
+                .iter()
+                .any(|f| f.name.eq_ignore_ascii_case(&field.name))
+            {
+                return invalid(format!("duplicated name {}", field.name));
+            }
+        }
+        if let Some(json) = self.columns.iter().find(|c| c.type_ == TaosDataType::Json) {
+            return invalid(format!("json column {} is not allowed", json.name));
+        }
+        Ok(())
+    }
+
+    /// Changes needed to migrate `live` super table to this schema, `None` if it does not exist.
+    ///
+    /// Adds go before modifies and drops, so that the super table always has columns and tags.
+    /// Drops are only generated with [StableSchema::allow_drop].
+    pub fn diff(&self, live: Option<&TaosDescribe>) -> Result<Vec<SchemaChange>, Error> {
+        self.validate()?;
+        let live = match live {
+            Some(live) => live,
+            None => return Ok(vec![SchemaChange::Create]),
+        };
+        if live.tags.is_empty() {
+            return Err(Error::IncompatibleSchema(format!(
+                "{} is a normal table, not a super table",
+                self.name
+            )));
+        }
+        if let (Some(declared), Some(current)) = (self.columns.first(), live.cols.first()) {
+            if !declared.name.eq_ignore_ascii_case(&current.name) {
+                return Err(Error::IncompatibleSchema(format!(
+                    "first column {} could not be changed to {}",
+                    current.name, declared.name
+                )));
+            }
+        }
+        for column in &self.columns {
+            if find(&live.tags, &column.name).is_some() {
+                return Err(Error::IncompatibleSchema(format!(
+                    "tag {} could not be changed to column",
+                    column.name
+                )));
+            }
+        }
+        for tag in &self.tags {
+            if find(&live.cols, &tag.name).is_some() {
+                return Err(Error::IncompatibleSchema(format!(
+                    "column {} could not be changed to tag",
+                    tag.name
+                )));
+            }
+        }
+
+        let (add_tags, modify_tags, drop_tags) = diff_fields("tag", &self.tags, &live.tags)?;
+        let (add_cols, modify_cols, drop_cols) = diff_fields("column", &self.columns, &live.cols)?;
+        let (drop_tags, drop_cols) = if self.allow_drop {
+            (drop_tags, drop_cols)
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        let mut changes = Vec::new();
+        changes.extend(add_tags.into_iter().map(SchemaChange::AddTag));
+        changes.extend(modify_tags.into_iter().map(SchemaChange::ModifyTag));
+        changes.extend(drop_tags.into_iter().map(SchemaChange::DropTag));
+        changes.extend(add_cols.into_iter().map(SchemaChange::AddColumn));
+        changes.extend(modify_cols.into_iter().map(SchemaChange::ModifyColumn));
+        changes.extend(drop_cols.into_iter().map(SchemaChange::DropColumn));
+        Ok(changes)
+    }
+
+    /// SQL of creating the super table.
+    pub fn create_sql(&self) -> Result<String, Error> {
+        let fields = |fields: &[ColumnMeta]| -> Result<String, Error> {
+            Ok(fields
+                .iter()
+                .map(field_sql)
+                .collect::<Result<Vec<_>, _>>()?
+                .join(", "))
+        };
+        Ok(format!(
+            "create stable if not exists {} ({}) tags ({})",
+            sql::ident(&self.name)?,
+            fields(&self.columns)?,
+            fields(&self.tags)?
+        ))
+    }
+
+    /// SQL of a change to this super table.
+    pub fn change_sql(&self, change: &SchemaChange) -> Result<String, Error> {
+        let stable = sql::ident(&self.name)?;
+        Ok(match change {
+            SchemaChange::Create => return self.create_sql(),
+            SchemaChange::AddColumn(meta) => {
+                format!("alter stable {} add column {}", stable, field_sql(meta)?)
+            }
+            SchemaChange::ModifyColumn(meta) => {
+                format!("alter stable {} modify column {}", stable, field_sql(meta)?)
+            }
+            SchemaChange::DropColumn(name) => {
+                format!("alter stable {} drop column {}", stable, sql::ident(name)?)
+            }
+            SchemaChange::AddTag(meta) => {
+                format!("alter stable {} add tag {}", stable, field_sql(meta)?)
+            }
+            SchemaChange::ModifyTag(meta) => {
+                format!("alter stable {} modify tag {}", stable, field_sql(meta)?)
+            }
+            SchemaChange::DropTag(name) => {
+                format!("alter stable {} drop tag {}", stable, sql::ident(name)?)
+            }
+        })
+    }
+
+    /// DDL statements to migrate `live` super table to this schema, see [StableSchema::diff].
+    pub fn statements(&self, live: Option<&TaosDescribe>) -> Result<Vec<String>, Error> {
+        self.diff(live)?
+            .iter()
+            .map(|change| self.change_sql(change))
+            .collect()
+    }
+
+    /// Dry run, describe the super table and return the DDL statements without executing.
+    ///
+    /// An existing normal table of the name is rejected by [Error::IncompatibleSchema].
+    pub async fn plan<C: TaosClient + ?Sized>(&self, taos: &C) -> Result<Vec<String>, Error> {
+        let live = match taos.describe(&self.name).await {
+            Ok(live) => Some(live),
+            Err(Error::RawTaosError(err)) if err.code == TaosCode::MndInvalidTableName => None,
+            Err(err) => return Err(err),
+        };
+        self.statements(live.as_ref())
+    }
+
+    /// Migrate the super table to this schema, returns the executed DDL statements.
+    pub async fn migrate<C: TaosClient + ?Sized>(&self, taos: &C) -> Result<Vec<String>, Error> {
+        let statements = self.plan(taos).await?;
+        for sql in &statements {
+            taos.exec(sql).await?;
+        }
+        Ok(statements)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn schema() -> StableSchema {
+        StableSchema::new(
+            "meters",
+            vec![
+                ColumnMeta::new("ts", TaosDataType::Timestamp, 8),
+                ColumnMeta::new("current", TaosDataType::Float, 4),
+                ColumnMeta::new("note", TaosDataType::Binary, 32),
+            ],
+            vec![
+                ColumnMeta::new("location", TaosDataType::Binary, 64),
+                ColumnMeta::new("group_id", TaosDataType::Int, 4),
+            ],
+        )
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test DDL of creating and altering super table
+    fn schema_ddl() -> Result<(), Error> {
+        let schema = schema();
+        assert_eq!(
+            schema.statements(None)?,
+            vec!["create stable if not exists meters (ts timestamp, current float, note binary(32)) tags (location binary(64), group_id int)"]
+        );
+
+        let live = TaosDescribe {
+            cols: vec![
+                ColumnMeta::new("ts", TaosDataType::Timestamp, 8),
+                ColumnMeta::new("voltage", TaosDataType::Int, 4),
+                ColumnMeta::new("note", TaosDataType::Binary, 16),
+            ],
+            tags: vec![ColumnMeta::new("location", TaosDataType::Binary, 64)],
+        };
+        assert_eq!(
+            schema.statements(Some(&live))?,
+            vec![
+                "alter stable meters add tag group_id int",
+                "alter stable meters add column current float",
+                "alter stable meters modify column note binary(32)",
+            ]
+        );
+        assert_eq!(
+            schema.clone().allow_drop(true).statements(Some(&live))?,
+            vec![
+                "alter stable meters add tag group_id int",
+                "alter stable meters add column current float",
+                "alter stable meters modify column note binary(32)",
+                "alter stable meters drop column voltage",
+            ]
+        );
+
+        let live = TaosDescribe {
+            cols: schema.columns.clone(),
+            tags: schema.tags.clone(),
+        };
+        assert!(schema.diff(Some(&live))?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test invalid schema and incompatible changes
+    fn schema_incompatible() {
+        let mut invalid = schema();
+        invalid.columns.remove(0);
+        assert!(matches!(invalid.diff(None), Err(Error::InvalidSchema(_))));
+        let mut invalid = schema();
+        invalid
+            .tags
+            .push(ColumnMeta::new("NOTE", TaosDataType::Int, 4));
+        assert!(matches!(invalid.diff(None), Err(Error::InvalidSchema(_))));
+
+        let schema = schema();
+        let changed = |cols: Vec<ColumnMeta>| TaosDescribe {
+            cols,
+            tags: schema.tags.clone(),
+        };
+        let shrunk = changed(vec![
+            ColumnMeta::new("ts", TaosDataType::Timestamp, 8),
+            ColumnMeta::new("note", TaosDataType::Binary, 64),
+        ]);
+        let retyped = changed(vec![
+            ColumnMeta::new("ts", TaosDataType::Timestamp, 8),
+            ColumnMeta::new("current", TaosDataType::Double, 8),
+        ]);
+        let renamed = changed(vec![ColumnMeta::new("time", TaosDataType::Timestamp, 8)]);
+        let normal = TaosDescribe {
+            cols: schema.columns.clone(),
+            tags: Vec::new(),
+        };
+        for live in [shrunk, retyped, renamed, normal] {
+            assert!(matches!(
+                schema.diff(Some(&live)),
+                Err(Error::IncompatibleSchema(_))
+            ));
+        }
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test migrating super table with native connection
+    async fn migrate_stable() -> Result<(), Error> {
+        let taos = crate::test::taos()?;
+        let db = "rs_migrate_stable";
+        taos.exec(format!("drop database if exists {}", db)).await?;
+        taos.exec(format!("create database if not exists {}", db))
+            .await?;
+        taos.exec(format!("use {}", db)).await?;
+
+        let mut schema = schema();
+        schema.tags.pop();
+        assert_eq!(schema.plan(&taos).await?.len(), 1);
+        schema.migrate(&taos).await?;
+
+        let schema = self::schema();
+        assert_eq!(schema.plan(&taos).await?.len(), 1);
+        schema.migrate(&taos).await?;
+        assert!(schema.plan(&taos).await?.is_empty());
+
+        // undeclared columns are kept unless drops are allowed.
+        let mut schema = self::schema();
+        schema.columns.pop();
+        assert!(schema.plan(&taos).await?.is_empty());
+        let schema = schema.allow_drop(true);
+        assert_eq!(schema.plan(&taos).await?.len(), 1);
+        schema.migrate(&taos).await?;
+
+        taos.exec("create table tb1 (ts timestamp, v int)").await?;
+        let normal = StableSchema::new("tb1", schema.columns.clone(), schema.tags.clone());
+        assert!(matches!(
+            normal.plan(&taos).await,
+            Err(Error::IncompatibleSchema(_))
+        ));
+
+        taos.exec(format!("drop database {}", db)).await?;
+        Ok(())
+    }
+}
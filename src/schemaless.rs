@@ -1,6 +1,15 @@
+#[cfg(all(feature = "native", feature = "schemaless"))]
 use crate::bindings::taos_schemaless_insert;
+#[cfg(all(feature = "native", feature = "schemaless"))]
 use crate::*;
-use crate::{CTaosResult, Taos};
+
+pub mod line;
+pub use line::{LineBuilder, Point};
+pub mod opentsdb;
+pub use opentsdb::{OpenTsdbJsonPoint, TelnetPoint, TsdbValue};
+mod report;
+pub(crate) use report::bisect;
+pub use report::{LineError, SchemalessResult};
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -39,6 +48,7 @@ pub const TSDB_SML_TIMESTAMP_MICROSECONDS: TSDB_SML_TIMESTAMP_TYPE =
 pub const TSDB_SML_TIMESTAMP_NANOSECONDS: TSDB_SML_TIMESTAMP_TYPE =
     TSDB_SML_TIMESTAMP_TYPE::Nanoseconds;
 
+#[cfg(all(feature = "native", feature = "schemaless"))]
 impl Taos {
     /// Schemaless insert with different protocol and timestamp precision.
     ///
@@ -95,14 +105,14 @@ impl Taos {
                 precision as _,
             );
 
-            let res = CTaosResult::new(res)?;
+            let res = CTaosResult::new(res).map_err(|err| self.check_error(err))?;
 
             Ok(res.affected_rows())
         }
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "native", feature = "schemaless"))]
 mod test {
     use crate::schemaless::*;
     use crate::test::taos;
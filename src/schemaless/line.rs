@@ -0,0 +1,336 @@
+//! Typed builder of InfluxDB line protocol.
+//!
+//! ```ignore
+//! let mut builder = LineBuilder::new(TimestampPrecision::Milli);
+//! builder.push(
+//!     &Point::new("meters")
+//!         .tag("location", "beijing")
+//!         .field("current", 10.3f32)
+//!         .field("note", "a, b")
+//!         .timestamp(Timestamp::now()),
+//! )?;
+//! taos.schemaless_insert(builder.lines(), TSDB_SML_LINE_PROTOCOL, builder.timestamp_type())?;
+//! ```
+use super::*;
+use crate::{Error, Field, IntoField, Timestamp, TimestampPrecision};
+
+use bstr::ByteSlice;
+use std::fmt::Write;
+
+impl From<TimestampPrecision> for TSDB_SML_TIMESTAMP_TYPE {
+    fn from(precision: TimestampPrecision) -> Self {
+        match precision {
+            TimestampPrecision::Milli => TSDB_SML_TIMESTAMP_MILLISECONDS,
+            TimestampPrecision::Micro => TSDB_SML_TIMESTAMP_MICROSECONDS,
+            TimestampPrecision::Nano => TSDB_SML_TIMESTAMP_NANOSECONDS,
+            TimestampPrecision::Unknown => TSDB_SML_TIMESTAMP_NOT_CONFIGURED,
+        }
+    }
+}
+
+fn invalid(msg: String) -> Error {
+    Error::InvalidSchemaless(msg)
+}
+
+/// Escape `chars` with backslash, line breaks are not allowed.
+fn escape(kind: &str, s: &str, chars: &[char]) -> Result<String, Error> {
+    if s.is_empty() {
+        return Err(invalid(format!("empty {}", kind)));
+    }
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c == '\n' || c == '\r' {
+            return Err(invalid(format!("line break in {} {:?}", kind, s)));
+        }
+        if chars.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    Ok(escaped)
+}
+
+fn escape_measurement(s: &str) -> Result<String, Error> {
+    escape("measurement", s, &[',', ' '])
+}
+
+fn escape_key(kind: &str, s: &str) -> Result<String, Error> {
+    escape(kind, s, &[',', '=', ' '])
+}
+
+fn quote(s: &str) -> Result<String, Error> {
+    if s.contains(&['\n', '\r'][..]) {
+        return Err(invalid(format!("line break in string field {:?}", s)));
+    }
+    Ok(format!(
+        "\"{}\"",
+        s.replace('\\', "\\\\").replace('"', "\\\"")
+    ))
+}
+
+/// Field value with type suffix, like `1i32`, `1.5f64`, `"abc"` and `L"abc"`.
+fn field_value(key: &str, value: &Field) -> Result<String, Error> {
+    let finite = |finite: bool| {
+        if finite {
+            Ok(())
+        } else {
+            Err(invalid(format!("field {} is not a finite number", key)))
+        }
+    };
+    Ok(match value {
+        Field::Bool(v) => v.to_string(),
+        Field::TinyInt(v) => format!("{}i8", v),
+        Field::SmallInt(v) => format!("{}i16", v),
+        Field::Int(v) => format!("{}i32", v),
+        Field::BigInt(v) => format!("{}i64", v),
+        Field::UTinyInt(v) => format!("{}u8", v),
+        Field::USmallInt(v) => format!("{}u16", v),
+        Field::UInt(v) => format!("{}u32", v),
+        Field::UBigInt(v) => format!("{}u64", v),
+        Field::Float(v) => {
+            finite(v.is_finite())?;
+            format!("{}f32", v)
+        }
+        Field::Double(v) => {
+            finite(v.is_finite())?;
+            format!("{}f64", v)
+        }
+        Field::Binary(v) => quote(
+            v.to_str()
+                .map_err(|_| invalid(format!("field {} is not valid utf-8", key)))?,
+        )?,
+        Field::NChar(v) => format!("L{}", quote(v)?),
+        v => {
+            return Err(invalid(format!(
+                "unsupported value of field {}: {:?}",
+                key, v
+            )))
+        }
+    })
+}
+
+/// A data point of InfluxDB line protocol.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point {
+    measurement: String,
+    tags: Vec<(String, String)>,
+    fields: Vec<(String, Field)>,
+    timestamp: Option<Timestamp>,
+}
+
+impl Point {
+    pub fn new(measurement: impl Into<String>) -> Self {
+        Self {
+            measurement: measurement.into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
+            timestamp: None,
+        }
+    }
+
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// Add a field, `&str` and `String` are nchar, use [bstr::BString] for binary.
+    pub fn field(mut self, key: impl Into<String>, value: impl IntoField) -> Self {
+        self.fields.push((key.into(), value.into_field()));
+        self
+    }
+
+    /// Timestamp of the point, server time is used if not set.
+    pub fn timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Render as a line with timestamp in `precision`.
+    pub fn to_line(&self, precision: TimestampPrecision) -> Result<String, Error> {
+        if self.fields.is_empty() {
+            return Err(invalid(format!(
+                "no field in measurement {}",
+                self.measurement
+            )));
+        }
+        let mut line = escape_measurement(&self.measurement)?;
+        for (key, value) in &self.tags {
+            write!(
+                line,
+                ",{}={}",
+                escape_key("tag key", key)?,
+                escape_key("tag value", value)?
+            )
+            .unwrap();
+        }
+        for (i, (key, value)) in self.fields.iter().enumerate() {
+            write!(
+                line,
+                "{}{}={}",
+                if i == 0 { ' ' } else { ',' },
+                escape_key("field key", key)?,
+                field_value(key, value)?
+            )
+            .unwrap();
+        }
+        if let Some(ts) = &self.timestamp {
+            let ts = ts.to_precision(precision).ok_or_else(|| {
+                invalid(format!(
+                    "timestamp {} could not be converted to {:?}",
+                    ts.timestamp, precision
+                ))
+            })?;
+            write!(line, " {}", ts.as_raw_timestamp()).unwrap();
+        }
+        Ok(line)
+    }
+}
+
+/// Lines of points in the same timestamp precision, for [schemaless_insert].
+///
+/// [schemaless_insert]: crate::Taos::schemaless_insert
+#[derive(Debug, Clone)]
+pub struct LineBuilder {
+    precision: TimestampPrecision,
+    lines: Vec<String>,
+}
+
+impl LineBuilder {
+    pub fn new(precision: TimestampPrecision) -> Self {
+        Self {
+            precision,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Render and append a point, timestamp is converted to the builder's precision.
+    pub fn push(&mut self, point: &Point) -> Result<&mut Self, Error> {
+        self.lines.push(point.to_line(self.precision)?);
+        Ok(self)
+    }
+
+    pub fn precision(&self) -> TimestampPrecision {
+        self.precision
+    }
+
+    /// Timestamp type for [schemaless_insert].
+    ///
+    /// [schemaless_insert]: crate::Taos::schemaless_insert
+    pub fn timestamp_type(&self) -> TSDB_SML_TIMESTAMP_TYPE {
+        self.precision.into()
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn into_lines(self) -> Vec<String> {
+        self.lines
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bstr::BString;
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test rendering points to escaped line protocol
+    fn render_line() -> Result<(), Error> {
+        let point = Point::new("st 1,a")
+            .tag("t 1", "a=b,c")
+            .tag("t2", "abc")
+            .field("c1", 3i64)
+            .field("c2", false)
+            .field("c3", "pa\"ss\\")
+            .field("c4", BString::from("bin"))
+            .field("c5", 4f64)
+            .field("c6", 1.5f32)
+            .field("c7", 1u8)
+            .timestamp(Timestamp::new(1626006833639, TimestampPrecision::Milli));
+        assert_eq!(
+            point.to_line(TimestampPrecision::Nano)?,
+            r#"st\ 1\,a,t\ 1=a\=b\,c,t2=abc c1=3i64,c2=false,c3=L"pa\"ss\\",c4="bin",c5=4f64,c6=1.5f32,c7=1u8 1626006833639000000"#
+        );
+
+        let mut builder = LineBuilder::new(TimestampPrecision::Milli);
+        builder
+            .push(
+                &Point::new("st")
+                    .field("v", 1i32)
+                    .timestamp(Timestamp::new(1626006833639999, TimestampPrecision::Micro)),
+            )?
+            .push(&Point::new("st").field("v", 2i32))?;
+        assert_eq!(builder.lines(), ["st v=1i32 1626006833639", "st v=2i32"]);
+        assert!(matches!(
+            builder.timestamp_type(),
+            TSDB_SML_TIMESTAMP_TYPE::Milliseconds
+        ));
+
+        let invalid = [
+            Point::new("st"),
+            Point::new("").field("v", 1i32),
+            Point::new("st").tag("t", "").field("v", 1i32),
+            Point::new("st").field("v", "a\nb"),
+            Point::new("st").field("v", f64::NAN),
+        ];
+        for point in invalid {
+            assert!(matches!(
+                point.to_line(TimestampPrecision::Milli),
+                Err(Error::InvalidSchemaless(_))
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(all(feature = "native", feature = "schemaless"))]
+    #[tokio::test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test schemaless insert with points
+    async fn point_insert() -> Result<(), Error> {
+        let taos = crate::test::taos()?;
+        let db = "rs_test_sml_point";
+        taos.exec(format!("drop database if exists {}", db)).await?;
+        taos.exec(format!("create database if not exists {} keep 36500", db))
+            .await?;
+        taos.exec(format!("use {}", db)).await?;
+
+        let mut builder = LineBuilder::new(TimestampPrecision::Micro);
+        for i in 0..3 {
+            builder.push(
+                &Point::new("st")
+                    .tag("location", "bei jing, chaoyang")
+                    .field("v", i as i32)
+                    .field("note", "a \"b\", c=d")
+                    .timestamp(Timestamp::new(1626006833639 + i, TimestampPrecision::Milli)),
+            )?;
+        }
+        let res = taos.schemaless_insert(
+            builder.lines(),
+            TSDB_SML_LINE_PROTOCOL,
+            builder.timestamp_type(),
+        )?;
+        assert_eq!(res, 3);
+
+        let res = taos.query("select note, location from st").await?;
+        assert_eq!(res.rows.len(), 3);
+        assert_eq!(res.rows[0][0], Field::NChar("a \"b\", c=d".into()));
+        assert_eq!(res.rows[0][1], Field::NChar("bei jing, chaoyang".into()));
+
+        taos.exec(format!("drop database {}", db)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,410 @@
+//! Data models of OpenTSDB telnet and JSON protocols.
+//!
+//! ```ignore
+//! let point = TelnetPoint::new("sys.if.bytes.out", 1479496100, 1.3e3)
+//!     .tag("host", "web01")
+//!     .tag("interface", "eth0");
+//! taos.schemaless_insert(&[point.to_line()?], TSDB_SML_TELNET_PROTOCOL, TSDB_SML_TIMESTAMP_SECONDS)?;
+//!
+//! let point = OpenTsdbJsonPoint::new("sys.cpu", 1626006833, 10).tag("host", "web01");
+//! taos.schemaless_insert(&[point.to_json()?], TSDB_SML_JSON_PROTOCOL, TSDB_SML_TIMESTAMP_SECONDS)?;
+//! ```
+use crate::Error;
+
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+fn invalid(msg: String) -> Error {
+    Error::InvalidSchemaless(msg)
+}
+
+/// Metric names, tag keys and telnet tag values only contain letters, digits and `-_./`.
+fn check_name(kind: &str, name: &str) -> Result<(), Error> {
+    if name.is_empty() {
+        return Err(invalid(format!("empty {}", kind)));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || "-_./".contains(c))
+    {
+        return Err(invalid(format!("invalid {} {:?}", kind, name)));
+    }
+    Ok(())
+}
+
+fn check_point<V>(metric: &str, timestamp: i64, tags: &BTreeMap<String, V>) -> Result<(), Error> {
+    check_name("metric", metric)?;
+    if timestamp < 0 {
+        return Err(invalid(format!(
+            "negative timestamp {} of metric {}",
+            timestamp, metric
+        )));
+    }
+    if tags.is_empty() {
+        return Err(invalid(format!("no tag in metric {}", metric)));
+    }
+    tags.keys().try_for_each(|key| check_name("tag key", key))
+}
+
+/// A data point of OpenTSDB telnet protocol, like
+/// `sys.if.bytes.out 1479496100 1.3E3 host=web01 interface=eth0`.
+///
+/// Timestamp is in seconds or milliseconds, as the precision passed to schemaless insert.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelnetPoint {
+    pub metric: String,
+    pub timestamp: i64,
+    pub value: f64,
+    pub tags: BTreeMap<String, String>,
+}
+
+impl TelnetPoint {
+    pub fn new(metric: impl Into<String>, timestamp: i64, value: f64) -> Self {
+        Self {
+            metric: metric.into(),
+            timestamp,
+            value,
+            tags: BTreeMap::new(),
+        }
+    }
+
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    /// Check naming rules, at least one tag is required.
+    pub fn validate(&self) -> Result<(), Error> {
+        check_point(&self.metric, self.timestamp, &self.tags)?;
+        if !self.value.is_finite() {
+            return Err(invalid(format!(
+                "value of metric {} is not a finite number",
+                self.metric
+            )));
+        }
+        self.tags
+            .values()
+            .try_for_each(|value| check_name("tag value", value))
+    }
+
+    /// Validate and render as a telnet line.
+    pub fn to_line(&self) -> Result<String, Error> {
+        self.validate()?;
+        let mut line = format!("{} {} {}", self.metric, self.timestamp, self.value);
+        for (key, value) in &self.tags {
+            line.push_str(&format!(" {}={}", key, value));
+        }
+        Ok(line)
+    }
+}
+
+impl FromStr for TelnetPoint {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || invalid(format!("malformed telnet line {:?}", s));
+        let mut parts = s.split_whitespace();
+        let metric = parts.next().ok_or_else(malformed)?;
+        let timestamp = parts
+            .next()
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(malformed)?;
+        let value = parts
+            .next()
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(malformed)?;
+        let mut point = TelnetPoint::new(metric, timestamp, value);
+        for tag in parts {
+            let (key, value) = tag.split_once('=').ok_or_else(malformed)?;
+            point = point.tag(key, value);
+        }
+        point.validate()?;
+        Ok(point)
+    }
+}
+
+impl Serialize for TelnetPoint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let line = self.to_line().map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&line)
+    }
+}
+
+impl<'de> Deserialize<'de> for TelnetPoint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let line = String::deserialize(deserializer)?;
+        line.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Value or tag value of OpenTSDB JSON protocol.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TsdbValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl From<bool> for TsdbValue {
+    fn from(v: bool) -> Self {
+        TsdbValue::Bool(v)
+    }
+}
+
+impl From<i64> for TsdbValue {
+    fn from(v: i64) -> Self {
+        TsdbValue::Int(v)
+    }
+}
+
+impl From<i32> for TsdbValue {
+    fn from(v: i32) -> Self {
+        TsdbValue::Int(v as _)
+    }
+}
+
+impl From<f64> for TsdbValue {
+    fn from(v: f64) -> Self {
+        TsdbValue::Float(v)
+    }
+}
+
+impl From<&str> for TsdbValue {
+    fn from(v: &str) -> Self {
+        TsdbValue::String(v.to_string())
+    }
+}
+
+impl From<String> for TsdbValue {
+    fn from(v: String) -> Self {
+        TsdbValue::String(v)
+    }
+}
+
+impl fmt::Display for TsdbValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TsdbValue::Bool(v) => write!(f, "{}", v),
+            TsdbValue::Int(v) => write!(f, "{}", v),
+            TsdbValue::Float(v) => write!(f, "{}", v),
+            TsdbValue::String(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// A data point of OpenTSDB JSON protocol.
+///
+/// Timestamp is in seconds or milliseconds, as the precision passed to schemaless insert.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "RawJsonPoint")]
+pub struct OpenTsdbJsonPoint {
+    pub metric: String,
+    pub timestamp: i64,
+    pub value: TsdbValue,
+    pub tags: BTreeMap<String, TsdbValue>,
+}
+
+/// Deserialized fields of [OpenTsdbJsonPoint] before validation.
+#[derive(Deserialize)]
+struct RawJsonPoint {
+    metric: String,
+    timestamp: i64,
+    value: TsdbValue,
+    tags: BTreeMap<String, TsdbValue>,
+}
+
+impl TryFrom<RawJsonPoint> for OpenTsdbJsonPoint {
+    type Error = Error;
+
+    fn try_from(raw: RawJsonPoint) -> Result<Self, Self::Error> {
+        let point = OpenTsdbJsonPoint {
+            metric: raw.metric,
+            timestamp: raw.timestamp,
+            value: raw.value,
+            tags: raw.tags,
+        };
+        point.validate()?;
+        Ok(point)
+    }
+}
+
+impl Serialize for OpenTsdbJsonPoint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.validate().map_err(serde::ser::Error::custom)?;
+        let mut point = serializer.serialize_struct("OpenTsdbJsonPoint", 4)?;
+        point.serialize_field("metric", &self.metric)?;
+        point.serialize_field("timestamp", &self.timestamp)?;
+        point.serialize_field("value", &self.value)?;
+        point.serialize_field("tags", &self.tags)?;
+        point.end()
+    }
+}
+
+impl OpenTsdbJsonPoint {
+    pub fn new(metric: impl Into<String>, timestamp: i64, value: impl Into<TsdbValue>) -> Self {
+        Self {
+            metric: metric.into(),
+            timestamp,
+            value: value.into(),
+            tags: BTreeMap::new(),
+        }
+    }
+
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<TsdbValue>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    /// Check naming rules, at least one tag is required.
+    pub fn validate(&self) -> Result<(), Error> {
+        check_point(&self.metric, self.timestamp, &self.tags)?;
+        let finite = |v: &TsdbValue| !matches!(v, TsdbValue::Float(v) if !v.is_finite());
+        if !finite(&self.value) || !self.tags.values().all(finite) {
+            return Err(invalid(format!(
+                "value of metric {} is not a finite number",
+                self.metric
+            )));
+        }
+        Ok(())
+    }
+
+    /// Validate and render as a JSON object.
+    pub fn to_json(&self) -> Result<String, Error> {
+        self.validate()?;
+        serde_json::to_string(self).map_err(|err| invalid(err.to_string()))
+    }
+
+    /// Validate and render points as a JSON array, which is inserted as a whole.
+    pub fn to_json_array(points: &[OpenTsdbJsonPoint]) -> Result<String, Error> {
+        points.iter().try_for_each(|point| point.validate())?;
+        serde_json::to_string(points).map_err(|err| invalid(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test OpenTSDB telnet line rendering, parsing and validation
+    fn telnet_point() -> Result<(), Error> {
+        let point = TelnetPoint::new("sys.if.bytes.out", 1479496100, 1.3e3)
+            .tag("interface", "eth0")
+            .tag("host", "web01");
+        let line = point.to_line()?;
+        assert_eq!(
+            line,
+            "sys.if.bytes.out 1479496100 1300 host=web01 interface=eth0"
+        );
+        assert_eq!(line.parse::<TelnetPoint>()?, point);
+        assert_eq!(
+            "sys.if.bytes.out 1479496100 1.3E3 host=web01 interface=eth0".parse::<TelnetPoint>()?,
+            point
+        );
+        assert_eq!(
+            serde_json::to_string(&point).unwrap(),
+            format!("{:?}", line)
+        );
+
+        let invalid = [
+            TelnetPoint::new("sys cpu", 1, 1.).tag("host", "web01"),
+            TelnetPoint::new("sys.cpu", 1, 1.),
+            TelnetPoint::new("sys.cpu", 1, 1.).tag("host", "web 01"),
+            TelnetPoint::new("sys.cpu", 1, 1.).tag("host=", "web01"),
+            TelnetPoint::new("sys.cpu", -1, 1.).tag("host", "web01"),
+            TelnetPoint::new("sys.cpu", 1, f64::INFINITY).tag("host", "web01"),
+        ];
+        for point in invalid {
+            assert!(matches!(point.to_line(), Err(Error::InvalidSchemaless(_))));
+        }
+        assert!("sys.cpu 1479496100 abc host=web01"
+            .parse::<TelnetPoint>()
+            .is_err());
+        assert!("sys.cpu 1479496100 1 host".parse::<TelnetPoint>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test OpenTSDB JSON point rendering and validation
+    fn json_point() -> Result<(), Error> {
+        let point = OpenTsdbJsonPoint::new("st", 1626006833, 10)
+            .tag("t1", true)
+            .tag("t3", 10)
+            .tag("t4", "123_abc_.!@#$%^&*:;,./?|+-=()[]{}<>");
+        let json = point.to_json()?;
+        assert_eq!(
+            json,
+            r#"{"metric":"st","timestamp":1626006833,"value":10,"tags":{"t1":true,"t3":10,"t4":"123_abc_.!@#$%^&*:;,./?|+-=()[]{}<>"}}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<OpenTsdbJsonPoint>(&json).unwrap(),
+            point
+        );
+        assert!(OpenTsdbJsonPoint::to_json_array(&[point.clone(), point])?.starts_with("[{"));
+
+        let invalid = [
+            OpenTsdbJsonPoint::new("st", 1, 10),
+            OpenTsdbJsonPoint::new("s t", 1, 10).tag("t1", 1),
+            OpenTsdbJsonPoint::new("st", 1, 10).tag("t 1", 1),
+            OpenTsdbJsonPoint::new("st", 1, f64::NAN).tag("t1", 1),
+        ];
+        for point in invalid {
+            assert!(serde_json::to_string(&point).is_err());
+            assert!(matches!(point.to_json(), Err(Error::InvalidSchemaless(_))));
+        }
+        assert!(serde_json::from_str::<OpenTsdbJsonPoint>(
+            r#"{"metric":"s t","timestamp":1626006833,"value":10,"tags":{"t1":1}}"#
+        )
+        .is_err());
+        assert!(serde_json::from_str::<OpenTsdbJsonPoint>(
+            r#"{"metric":"st","timestamp":1626006833,"value":10,"tags":{}}"#
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[cfg(all(feature = "native", feature = "schemaless"))]
+    #[tokio::test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test schemaless insert with OpenTSDB points
+    async fn opentsdb_point_insert() -> Result<(), Error> {
+        use crate::schemaless::*;
+
+        let taos = crate::test::taos()?;
+        let db = "rs_test_sml_opentsdb";
+        taos.exec(format!("drop database if exists {}", db)).await?;
+        taos.exec(format!("create database if not exists {} keep 36500", db))
+            .await?;
+        taos.exec(format!("use {}", db)).await?;
+
+        let lines: Vec<_> = (0..4)
+            .map(|i| {
+                TelnetPoint::new("sys.if.bytes.out", 1479496100 + i, 1.3e3)
+                    .tag("host", format!("web0{}", i))
+                    .to_line()
+            })
+            .collect::<Result<_, _>>()?;
+        let res =
+            taos.schemaless_insert(&lines, TSDB_SML_TELNET_PROTOCOL, TSDB_SML_TIMESTAMP_SECONDS)?;
+        assert_eq!(res, 4);
+
+        let point = OpenTsdbJsonPoint::new("st", 1626006833, 10).tag("t1", "abc");
+        let res = taos.schemaless_insert(
+            &[point.to_json()?],
+            TSDB_SML_JSON_PROTOCOL,
+            TSDB_SML_TIMESTAMP_SECONDS,
+        )?;
+        assert_eq!(res, 1);
+
+        taos.exec(format!("drop database {}", db)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+//! Per-line report of schemaless insert, see [TaosClient::schemaless_insert_bisect].
+//!
+//! [TaosClient::schemaless_insert_bisect]: crate::TaosClient::schemaless_insert_bisect
+use crate::{Error, TaosCode};
+
+use std::future::Future;
+use std::ops::Range;
+
+/// A line rejected by server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineError {
+    /// Index of the line in the batch.
+    pub index: usize,
+    pub line: String,
+    pub code: TaosCode,
+    pub message: String,
+}
+
+/// Result of schemaless insert with failed lines isolated.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemalessResult {
+    /// Rows inserted by the accepted lines.
+    pub affected_rows: usize,
+    /// Rejected lines in the order of the batch.
+    pub errors: Vec<LineError>,
+}
+
+impl SchemalessResult {
+    /// Whether all lines are accepted.
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Insert `lines` by `insert`, split a failed batch in halves until the bad lines are isolated.
+///
+/// Fatal errors and errors not from server are returned immediately, since retrying smaller
+/// batches would not help.
+pub(crate) async fn bisect<'a, T, F, Fut>(
+    lines: &'a [T],
+    mut insert: F,
+) -> Result<SchemalessResult, Error>
+where
+    T: AsRef<str>,
+    F: FnMut(&'a [T]) -> Fut,
+    Fut: Future<Output = Result<i32, Error>>,
+{
+    let mut result = SchemalessResult::default();
+    // ranges to insert, the left halves are popped first to keep errors in order.
+    let mut pending: Vec<Range<usize>> = Vec::new();
+    pending.push(0..lines.len());
+    while let Some(range) = pending.pop() {
+        if range.is_empty() {
+            continue;
+        }
+        match insert(&lines[range.clone()]).await {
+            Ok(rows) => result.affected_rows += rows.max(0) as usize,
+            Err(Error::RawTaosError(err)) if !err.is_fatal() => {
+                if range.len() == 1 {
+                    result.errors.push(LineError {
+                        index: range.start,
+                        line: lines[range.start].as_ref().to_string(),
+                        code: err.code,
+                        message: err.err.to_string(),
+                    });
+                } else {
+                    let mid = range.start + range.len() / 2;
+                    pending.push(mid..range.end);
+                    pending.push(range.start..mid);
+                }
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TaosError;
+
+    fn reject(code: TaosCode, lines: &[&str]) -> Result<i32, Error> {
+        match lines.iter().find(|line| line.contains("bad")) {
+            Some(line) => Err(TaosError {
+                code,
+                err: format!("invalid line {}", line).into(),
+            }
+            .into()),
+            None => Ok(lines.len() as _),
+        }
+    }
+
+    #[tokio::test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test bisecting failed batch to isolate bad lines
+    async fn bisect_lines() -> Result<(), Error> {
+        let mut lines: Vec<String> = (0..100).map(|i| format!("st v={}i32", i)).collect();
+        lines[7] = "bad 7".to_string();
+        lines[8] = "bad 8".to_string();
+        lines[99] = "bad 99".to_string();
+        let mut calls = 0;
+        let result = bisect(&lines, |lines| {
+            calls += 1;
+            let lines: Vec<&str> = lines.iter().map(|line| line.as_str()).collect();
+            let res = reject(TaosCode::TscInvalidValue, &lines);
+            async move { res }
+        })
+        .await?;
+        assert_eq!(result.affected_rows, 97);
+        let indexes: Vec<usize> = result.errors.iter().map(|err| err.index).collect();
+        assert_eq!(indexes, vec![7, 8, 99]);
+        assert_eq!(result.errors[0].line, "bad 7");
+        assert_eq!(result.errors[0].code, TaosCode::TscInvalidValue);
+        assert!(calls < 40);
+
+        let lines = ["st v=1i32", "st v=2i32"];
+        let result = bisect(&lines, |lines| {
+            futures::future::ready(reject(TaosCode::TscInvalidValue, lines))
+        })
+        .await?;
+        assert!(result.is_ok());
+        assert_eq!(result.affected_rows, 2);
+
+        let lines = ["st v=1i32", "bad"];
+        let result = bisect(&lines, |lines| {
+            futures::future::ready(reject(TaosCode::TscDisconnected, lines))
+        })
+        .await;
+        assert!(matches!(result, Err(Error::RawTaosError(_))));
+        Ok(())
+    }
+
+    #[cfg(all(feature = "native", feature = "schemaless"))]
+    #[tokio::test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test schemaless insert with bad lines isolated
+    async fn line_insert_bisect() -> Result<(), Error> {
+        use crate::schemaless::*;
+        use crate::TaosClient;
+
+        let taos = crate::test::taos()?;
+        let db = "rs_test_sml_bisect";
+        taos.exec(format!("drop database if exists {}", db)).await?;
+        taos.exec(format!("create database if not exists {} keep 36500", db))
+            .await?;
+        taos.exec(format!("use {}", db)).await?;
+
+        let lines = [
+            "st,t1=a v=1i32 1626006833639",
+            "st,t1=a v= 1626006833640",
+            "st,t1=a v=3i32 1626006833641",
+        ];
+        let result = taos
+            .schemaless_insert_bisect(
+                &lines,
+                TSDB_SML_LINE_PROTOCOL,
+                TSDB_SML_TIMESTAMP_MILLISECONDS,
+            )
+            .await?;
+        assert_eq!(result.affected_rows, 2);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].index, 1);
+        assert_eq!(result.errors[0].line, lines[1]);
+
+        taos.exec(format!("drop database {}", db)).await?;
+        Ok(())
+    }
+}
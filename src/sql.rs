@@ -0,0 +1,280 @@
+//! Helpers to build SQL with escaped literals and quoted identifiers.
+//!
+//! ```ignore
+//! use libtaos::{sql, sql::Ident};
+//! let sql = sql!("select * from {} where name = {}", Ident::new("tb1")?, "abc'def")?;
+//! assert_eq!(sql, r"select * from tb1 where name = 'abc\'def'");
+//! ```
+use crate::*;
+
+use std::fmt;
+
+/// Render a value in TDengine SQL literal syntax, values without a literal such as NaN are
+/// rejected.
+pub trait ToSqlLiteral {
+    fn to_sql_literal(&self) -> Result<String, Error>;
+}
+
+/// Reserved words of TDengine, which are quoted when used as identifiers.
+const KEYWORDS: &str = "\
+    abort account accounts add after all alter and as asc attach before begin between bigint \
+    binary blocks bool by cache cachelast cascade change cluster column comp compact concat \
+    conflict connection connections conns copy create ctime database databases days dbs deferred \
+    delete delimiters desc describe detach distinct dnode dnodes double drop each end exists \
+    explain fail file fill float for from fsync function functions glob grants group having if \
+    ignore immediate import in initially insert instead int integer interval into is isnull join \
+    json keep key kill like limit linear local match maxrows minrows mnodes modify modules nchar \
+    none not notnull now null of offset or order outputtype partitions pass precision prev \
+    privilege qtime queries query quorum raise replace replica reset restrict row scores select \
+    session set sliding slimit smallint soffset stable stables state_window statement stream \
+    streams syncdb table tables tag tags timestamp tinyint topic topics trigger tseries union \
+    unsigned update use user users using values variable variables vgroups view vnodes wal where";
+
+/// Escape a string to be used in a single-quoted string literal.
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c == '\\' || c == '\'' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Quote a string as a string literal.
+pub fn quote(s: &str) -> String {
+    format!("'{}'", escape(s))
+}
+
+/// A valid identifier, such as database, table or column name.
+///
+/// Plain names are kept as is, others and reserved words are quoted with backticks, `db.table` is
+/// treated as qualified name and each part is quoted separately.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ident(String);
+
+impl Ident {
+    pub fn new(name: impl AsRef<str>) -> Result<Self, Error> {
+        let name = name.as_ref();
+        if name.is_empty() || name.contains('`') {
+            return Err(Error::InvalidIdentifier(name.to_string()));
+        }
+        let quoted = name
+            .split('.')
+            .map(|part| {
+                if part.is_empty() {
+                    return Err(Error::InvalidIdentifier(name.to_string()));
+                }
+                let plain = part
+                    .chars()
+                    .next()
+                    .map(|c| c.is_ascii_alphabetic() || c == '_')
+                    .unwrap_or(false)
+                    && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                    && !KEYWORDS
+                        .split_whitespace()
+                        .any(|keyword| keyword.eq_ignore_ascii_case(part));
+                Ok(if plain {
+                    part.to_string()
+                } else {
+                    format!("`{}`", part)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self(quoted.join(".")))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Ident {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Quote an identifier, see [Ident].
+pub fn ident(name: impl AsRef<str>) -> Result<Ident, Error> {
+    Ident::new(name)
+}
+
+impl ToSqlLiteral for Ident {
+    fn to_sql_literal(&self) -> Result<String, Error> {
+        Ok(self.0.clone())
+    }
+}
+
+impl ToSqlLiteral for str {
+    fn to_sql_literal(&self) -> Result<String, Error> {
+        Ok(quote(self))
+    }
+}
+
+impl ToSqlLiteral for String {
+    fn to_sql_literal(&self) -> Result<String, Error> {
+        Ok(quote(self))
+    }
+}
+
+impl<T: ToSqlLiteral + ?Sized> ToSqlLiteral for &T {
+    fn to_sql_literal(&self) -> Result<String, Error> {
+        (**self).to_sql_literal()
+    }
+}
+
+impl<T: ToSqlLiteral> ToSqlLiteral for Option<T> {
+    fn to_sql_literal(&self) -> Result<String, Error> {
+        match self {
+            Some(v) => v.to_sql_literal(),
+            None => Ok("NULL".to_string()),
+        }
+    }
+}
+
+macro_rules! _impl_display_sql_literal {
+    ($($ty:ty),*) => {
+        $(
+            impl ToSqlLiteral for $ty {
+                fn to_sql_literal(&self) -> Result<String, Error> {
+                    Ok(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+_impl_display_sql_literal!(bool, i8, i16, i32, i64, u8, u16, u32, u64);
+
+macro_rules! _impl_float_sql_literal {
+    ($($ty:ty),*) => {
+        $(
+            impl ToSqlLiteral for $ty {
+                /// NaN and infinity have no literal in TDengine.
+                fn to_sql_literal(&self) -> Result<String, Error> {
+                    if !self.is_finite() {
+                        return Err(Error::InvalidLiteral(self.to_string()));
+                    }
+                    Ok(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+_impl_float_sql_literal!(f32, f64);
+
+impl ToSqlLiteral for Timestamp {
+    /// Timestamps are rendered in RFC3339 format in UTC, so it's not affected by timezone
+    /// or precision of the database.
+    fn to_sql_literal(&self) -> Result<String, Error> {
+        let format = match self.precision {
+            TimestampPrecision::Nano => "%Y-%m-%dT%H:%M:%S%.9fZ",
+            TimestampPrecision::Micro => "%Y-%m-%dT%H:%M:%S%.6fZ",
+            _ => "%Y-%m-%dT%H:%M:%S%.3fZ",
+        };
+        Ok(format!("'{}'", self.to_naive_datetime().format(format)))
+    }
+}
+
+impl ToSqlLiteral for Field {
+    fn to_sql_literal(&self) -> Result<String, Error> {
+        Ok(match self {
+            Field::Null => "NULL".to_string(),
+            Field::Binary(v) => quote(&v.to_string()),
+            Field::NChar(v) => quote(v),
+            Field::Json(v) => quote(&v.to_string()),
+            Field::Timestamp(v) => return v.to_sql_literal(),
+            Field::Float(v) => return v.to_sql_literal(),
+            Field::Double(v) => return v.to_sql_literal(),
+            v => v.to_string(),
+        })
+    }
+}
+
+/// Format SQL with arguments rendered by [ToSqlLiteral](crate::sql::ToSqlLiteral), returns
+/// error if any argument has no literal.
+///
+/// Use [Ident](crate::sql::Ident) for identifiers.
+#[macro_export]
+macro_rules! sql {
+    ($fmt:expr $(, $arg:expr)* $(,)?) => {
+        (|| -> ::std::result::Result<String, $crate::Error> {
+            Ok(format!($fmt $(, $crate::sql::ToSqlLiteral::to_sql_literal(&$arg)?)*))
+        })()
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test string literal escaping
+    fn quote_literal() {
+        assert_eq!(quote("abc"), "'abc'");
+        assert_eq!(quote(r#"abc"def"#), r#"'abc"def'"#);
+        assert_eq!(quote(r"abc'def\"), r"'abc\'def\\'");
+        assert_eq!(Some("a").to_sql_literal().unwrap(), "'a'");
+        assert_eq!(None::<i32>.to_sql_literal().unwrap(), "NULL");
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test identifier quoting
+    fn quote_ident() {
+        assert_eq!(ident("tb1").unwrap().as_str(), "tb1");
+        assert_eq!(ident("db1.tb1").unwrap().as_str(), "db1.tb1");
+        assert_eq!(ident("Tb-1").unwrap().as_str(), "`Tb-1`");
+        assert_eq!(ident("db.1tb").unwrap().as_str(), "db.`1tb`");
+        assert_eq!(ident("select").unwrap().as_str(), "`select`");
+        assert_eq!(ident("db.From").unwrap().as_str(), "db.`From`");
+        assert!(ident("tb`; drop database db").is_err());
+        assert!(ident("").is_err());
+        assert!(ident("db.").is_err());
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test field and timestamp literal
+    fn field_literal() -> Result<(), Error> {
+        let ts = Timestamp::new(1626006833639, TimestampPrecision::Milli);
+        assert_eq!(ts.to_sql_literal()?, "'2021-07-11T12:33:53.639Z'");
+        let ts = Timestamp::new(1626006833639000001, TimestampPrecision::Nano);
+        assert_eq!(ts.to_sql_literal()?, "'2021-07-11T12:33:53.639000001Z'");
+        assert_eq!(Field::Null.to_sql_literal()?, "NULL");
+        assert_eq!(Field::Bool(true).to_sql_literal()?, "true");
+        assert_eq!(Field::Double(1.5).to_sql_literal()?, "1.5");
+        assert_eq!(Field::Binary("a'b".into()).to_sql_literal()?, r"'a\'b'");
+        assert_eq!(
+            Field::Json(serde_json::json!({"k": "v"})).to_sql_literal()?,
+            r#"'{"k":"v"}'"#
+        );
+        assert!(f64::NAN.to_sql_literal().is_err());
+        assert!(f32::INFINITY.to_sql_literal().is_err());
+        assert!(Field::Double(f64::NEG_INFINITY).to_sql_literal().is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test sql! macro
+    fn sql_macro() -> Result<(), Error> {
+        let sql = sql!(
+            "insert into {} values({}, {}, {})",
+            ident("tb1")?,
+            Timestamp::new(0, TimestampPrecision::Milli),
+            "abc'def",
+            None::<f64>,
+        )?;
+        assert_eq!(
+            sql,
+            r"insert into tb1 values('1970-01-01T00:00:00.000Z', 'abc\'def', NULL)"
+        );
+        assert!(sql!("insert into tb1 values({})", f64::NAN).is_err());
+        Ok(())
+    }
+}
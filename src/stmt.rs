@@ -7,6 +7,9 @@ use std::os::raw::c_void;
 mod bind;
 pub use bind::{BindParam, IntoBindParam};
 
+mod column;
+pub use column::ColumnView;
+
 pub trait IntoParams {
     fn into_params(self) -> Vec<BindParam>;
 }
@@ -20,10 +23,16 @@ impl<F: IntoBindParam, T: IntoIterator<Item = F>> IntoParams for T {
 pub struct Stmt {
     taos: *mut c_void,
     stmt: *mut c_void,
+    broken: BrokenFlag,
 }
 
 impl Stmt {
     fn err_or(&self, res: i32) -> Result<(), TaosError> {
+        self.stmt_error(res).map_err(|err| self.broken.check(err))
+    }
+
+    /// Error of the stmt call without marking the connection.
+    fn stmt_error(&self, res: i32) -> Result<(), TaosError> {
         if res != 0 {
             let code: TaosCode = (res & 0x0000ffff).into();
             let err = unsafe { taos_stmt_errstr(self.stmt) };
@@ -47,28 +56,20 @@ impl Stmt {
         self.err_or(res)
     }
     pub fn execute(&self) -> Result<(), TaosError> {
-        unsafe {
-            let res = taos_stmt_execute(self.stmt);
-            let res = self.err_or(res);
-
-            if let Err(TaosError { code, err }) = res {
-                if code == TaosCode::RpcFqdnError {
-                    let res = taos_query(self.taos, b"select server_version()\0" as *const u8 as _);
-                    let errno = taos_errno(res);
-                    if errno == 0 {
-                        return Ok(())
-                    } else {
-                        Err(TaosError { code, err })
-                    }
-                } else {
-                    Err(TaosError { code, err })
-                }
-            } else {
-                Ok(())
-            }
+        let res = unsafe { taos_stmt_execute(self.stmt) };
+        match self.stmt_error(res) {
+            // ignore the spurious fqdn error if server is still reachable.
+            Err(err) if err.code == TaosCode::RpcFqdnError && self.server_reachable() => Ok(()),
+            res => res.map_err(|err| self.broken.check(err)),
         }
     }
 
+    /// Probe the server with a query, the result set is freed on drop.
+    fn server_reachable(&self) -> bool {
+        let sql = b"select server_version()\0";
+        CTaosResult::new(unsafe { taos_query(self.taos, sql.as_ptr() as _) }).is_ok()
+    }
+
     /// To bind one row with params
     pub fn bind(&mut self, params: impl IntoParams) -> Result<(), TaosError> {
         let params = params.into_params();
@@ -82,6 +83,34 @@ impl Stmt {
         Ok(())
     }
 
+    /// Query with params, the result set is freed by the returned [CTaosResult].
+    ///
+    /// ```ignore
+    /// let mut stmt = taos.stmt("select * from tb1 where ts > ? and v = ?")?;
+    /// let data = stmt.query(vec![Field::Timestamp(ts), Field::Int(1)].iter())?.fetch_fields()?;
+    /// ```
+    pub fn query(&mut self, params: impl IntoParams) -> Result<CTaosResult, TaosError> {
+        let params = params.into_params();
+        unsafe {
+            let res = taos_stmt_bind_param(self.stmt, params.as_ptr() as _);
+            self.err_or(res)?;
+        }
+        self.execute()?;
+        self.use_result()
+    }
+
+    /// Result set of the executed statement.
+    pub fn use_result(&mut self) -> Result<CTaosResult, TaosError> {
+        let res = unsafe { taos_stmt_use_result(self.stmt) };
+        if res.is_null() {
+            // the error code is not returned, use the message from stmt.
+            self.err_or(TaosCode::TscAppError as _)?;
+        }
+        CTaosResult::new(res)
+            .map(|res| res.watch(&self.broken))
+            .map_err(|err| self.broken.check(err))
+    }
+
     /// Bind params for one record.
     pub fn bind_inplace(&mut self, params: &[BindParam]) -> Result<(), TaosError> {
         unsafe {
@@ -97,12 +126,55 @@ impl Stmt {
         Ok(())
     }
 
-    pub fn bind_batch_at_col<T>(&mut self, _params: T) -> Result<(), TaosError>
-    where
-        T: IntoIterator,
-        T::Item: IntoBindParam,
-    {
-        Ok(())
+    /// Bind multiple rows by columns, one column for each param, and all columns should have the
+    /// same number of rows.
+    ///
+    /// ```ignore
+    /// let mut stmt = taos.stmt("insert into tb1 values(?, ?)")?;
+    /// stmt.bind_batch(&[
+    ///     ColumnView::from(vec![Timestamp::now()]),
+    ///     ColumnView::from(vec![Some(1.0f64)]),
+    /// ])?;
+    /// stmt.execute()?;
+    /// ```
+    pub fn bind_batch(&mut self, columns: &[ColumnView]) -> Result<(), TaosError> {
+        // the client library reads as many binds as the params.
+        let params = self.num_params();
+        if columns.is_empty() || columns.len() != params {
+            return Err(TaosError {
+                code: TaosCode::TscInvalidValue,
+                err: format!("{} columns to bind, but {} params", columns.len(), params).into(),
+            });
+        }
+        if columns.iter().map(|col| col.len()).dedup().count() > 1 {
+            return Err(TaosError {
+                code: TaosCode::TscInvalidValue,
+                err: "columns to bind should have the same number of rows".into(),
+            });
+        }
+        let mut binds = columns.iter().map(|col| col.as_multi_bind()).collect_vec();
+        unsafe {
+            let res = taos_stmt_bind_param_batch(self.stmt, binds.as_mut_ptr());
+            self.err_or(res)?;
+        }
+        self.add_batch()
+    }
+
+    /// Bind multiple rows of column `col`, call [Stmt::add_batch] after all columns bound.
+    pub fn bind_batch_at_col(&mut self, col: usize, column: &ColumnView) -> Result<(), TaosError> {
+        let mut bind = column.as_multi_bind();
+        unsafe {
+            let res = taos_stmt_bind_single_param_batch(self.stmt, &mut bind as _, col as _);
+            self.err_or(res)
+        }
+    }
+
+    /// Add current bound rows to the batch.
+    pub fn add_batch(&mut self) -> Result<(), TaosError> {
+        unsafe {
+            let res = taos_stmt_add_batch(self.stmt);
+            self.err_or(res)
+        }
     }
 
     pub fn num_params(&self) -> usize {
@@ -166,7 +238,11 @@ impl Taos {
         unsafe {
             let stmt = taos_stmt_init(self.as_raw());
             // let res = taos_stmt_prepare(stmt, sql.as_ptr(), 0);
-            let mut stmt = Stmt { taos: self.as_raw(), stmt };
+            let mut stmt = Stmt {
+                taos: self.as_raw(),
+                stmt,
+                broken: self.broken_flag().clone(),
+            };
             stmt.prepare(sql)?;
             Ok(stmt)
         }
@@ -175,8 +251,10 @@ impl Taos {
 
 #[cfg(test)]
 mod test {
+    use crate::stmt::ColumnView;
     use crate::test::taos;
     use crate::*;
+    use itertools::Itertools;
     use proc_test_catalog::test_catalogue;
 
     async fn stmt_test(db: &str, ty: &str, value: Field) -> Result<(), Error> {
@@ -503,6 +581,95 @@ mod test {
         taos.exec(format!("drop database {}", db)).await?;
         Ok(())
     }
+    #[tokio::test]
+    #[test_catalogue()]
+    /// Test STMT inserting multiple rows with column views.
+    async fn bind_batch() -> Result<(), Error> {
+        let db = stdext::function_name!()
+            .replace("::{{closure}}", "")
+            .replace("::", "_");
+        let taos = taos()?;
+        taos.exec(format!("drop database if exists {}", db)).await?;
+        taos.exec(format!("create database if not exists {} keep 36500", db))
+            .await?;
+        taos.exec(format!("use {}", db)).await?;
+        taos.exec("create table tb0 (ts timestamp, v double, name nchar(10))")
+            .await?;
+
+        const ROWS: i64 = 1000;
+        let ts = (0..ROWS)
+            .map(|i| Timestamp::new(1626006833639 + i, TimestampPrecision::Milli))
+            .collect_vec();
+        let v = (0..ROWS)
+            .map(|i| if i % 2 == 0 { Some(i as f64) } else { None })
+            .collect_vec();
+        let names = (0..ROWS).map(|i| format!("n{}", i)).collect_vec();
+        let columns = [ts.into(), v.into(), names.into()];
+
+        let mut stmt = taos.stmt("insert into tb0 values(?, ?, ?)")?;
+        stmt.bind_batch(&columns)?;
+        stmt.execute()?;
+        let res = taos.query("select count(*), count(v) from tb0").await?;
+        assert_eq!(
+            res.rows[0],
+            vec![Field::BigInt(ROWS), Field::BigInt(ROWS / 2)]
+        );
+
+        let ts = vec![Timestamp::new(0, TimestampPrecision::Milli)];
+        let columns: [ColumnView; 3] = [ts.into(), vec![0.5f64].into(), vec!["a"].into()];
+        for (col, column) in columns.iter().enumerate() {
+            stmt.bind_batch_at_col(col, column)?;
+        }
+        stmt.add_batch()?;
+        stmt.execute()?;
+        let res = taos.query("select count(*) from tb0").await?;
+        assert_eq!(res.rows[0][0], Field::BigInt(ROWS + 1));
+
+        let ts = vec![Timestamp::new(1, TimestampPrecision::Milli)];
+        let columns: [ColumnView; 3] = [ts.into(), vec![0.5f64, 1.5].into(), vec!["a"].into()];
+        assert!(stmt.bind_batch(&columns).is_err());
+        let columns: [ColumnView; 2] = [vec![1i64].into(), vec![1i64].into()];
+        assert!(stmt.bind_batch(&columns).is_err());
+        assert!(stmt.bind_batch(&[]).is_err());
+
+        taos.exec(format!("drop database {}", db)).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_catalogue()]
+    /// Test STMT query with params.
+    async fn query() -> Result<(), Error> {
+        let db = stdext::function_name!()
+            .replace("::{{closure}}", "")
+            .replace("::", "_");
+        let taos = taos()?;
+        taos.exec(format!("drop database if exists {}", db)).await?;
+        taos.exec(format!("create database if not exists {} keep 36500", db))
+            .await?;
+        taos.exec(format!("use {}", db)).await?;
+        taos.exec("create table tb0 (ts timestamp, v int, name binary(10))")
+            .await?;
+        taos.exec("insert into tb0 values(1626006833639, 1, 'a') (1626006833640, 2, 'b') (1626006833641, 1, 'c')")
+            .await?;
+
+        let mut stmt = taos.stmt("select name from tb0 where ts > ? and v = ?")?;
+        assert!(!stmt.is_insert());
+        let ts = Field::Timestamp(Timestamp::new(1626006833639, TimestampPrecision::Milli));
+        let data = stmt.query(vec![ts, Field::Int(1)].iter())?.fetch_fields()?;
+        assert_eq!(data.rows, vec![vec![Field::Binary("c".into())]]);
+
+        // query again with other params
+        let rows = stmt
+            .query(vec![Field::BigInt(0), Field::Int(2)].iter())?
+            .rows()
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(rows.len(), 1);
+
+        taos.exec(format!("drop database {}", db)).await?;
+        Ok(())
+    }
+
     #[tokio::test]
     #[test_catalogue()]
     /// Test STMT set tbname with upper-case stable, see jira TD-12977
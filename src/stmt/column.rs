@@ -0,0 +1,356 @@
+use crate::bindings::*;
+use crate::*;
+
+use bstr::{BStr, BString};
+
+use std::os::raw::{c_char, c_void};
+
+/// Column-oriented values to bind multiple rows at once, by `taos_stmt_bind_param_batch`.
+///
+/// It could be built from vectors of values, `None` as null:
+///
+/// ```ignore
+/// let ts = ColumnView::from(vec![Timestamp::now()]);
+/// let v = ColumnView::from(vec![Some(1.0f64)]);
+/// let name = ColumnView::from(vec!["abc"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ColumnView {
+    ty: TaosDataType,
+    /// Values in fixed size slots, keep it 8 bytes aligned for any primitive type.
+    buffer: Vec<u64>,
+    buffer_length: usize,
+    lengths: Vec<i32>,
+    nulls: Vec<c_char>,
+}
+
+impl ColumnView {
+    fn with_capacity(ty: TaosDataType, buffer_length: usize, len: usize) -> Self {
+        Self {
+            ty,
+            buffer: vec![0; (buffer_length * len).div_ceil(8)],
+            buffer_length,
+            lengths: vec![0; len],
+            nulls: vec![0; len],
+        }
+    }
+
+    fn set(&mut self, i: usize, value: Option<&[u8]>) {
+        match value {
+            Some(value) => {
+                let offset = i * self.buffer_length;
+                let buffer = unsafe {
+                    std::slice::from_raw_parts_mut(
+                        self.buffer.as_mut_ptr() as *mut u8,
+                        self.buffer.len() * 8,
+                    )
+                };
+                buffer[offset..offset + value.len()].copy_from_slice(value);
+                self.lengths[i] = value.len() as _;
+            }
+            None => self.nulls[i] = 1,
+        }
+    }
+
+    /// Build from values of fixed length type.
+    fn from_fixed<T: Copy>(ty: TaosDataType, values: Vec<Option<T>>) -> Self {
+        let size = std::mem::size_of::<T>();
+        let mut view = Self::with_capacity(ty, size, values.len());
+        for (i, value) in values.iter().enumerate() {
+            let bytes = value
+                .as_ref()
+                .map(|v| unsafe { std::slice::from_raw_parts(v as *const T as *const u8, size) });
+            view.set(i, bytes);
+        }
+        view
+    }
+
+    /// Build from values of variable length type.
+    fn from_var<T: AsRef<[u8]>>(ty: TaosDataType, values: Vec<Option<T>>) -> Self {
+        let buffer_length = values
+            .iter()
+            .flatten()
+            .map(|v| v.as_ref().len())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let mut view = Self::with_capacity(ty, buffer_length, values.len());
+        for (i, value) in values.iter().enumerate() {
+            view.set(i, value.as_ref().map(|v| v.as_ref()));
+        }
+        view
+    }
+
+    /// A column of `len` null values.
+    pub fn nulls(ty: TaosDataType, len: usize) -> Self {
+        let mut view = Self::with_capacity(ty, 1, len);
+        view.nulls.iter_mut().for_each(|v| *v = 1);
+        view
+    }
+
+    /// Build a column of type `ty` from row values, `Field::Null` as null.
+    ///
+    /// Binary and nchar values are accepted by both binary and nchar columns, timestamps are
+    /// converted to `precision` of the database, other values should match the column type
+    /// exactly.
+    pub fn from_fields<'a>(
+        ty: TaosDataType,
+        precision: TimestampPrecision,
+        fields: impl IntoIterator<Item = &'a Field>,
+    ) -> Result<Self, TaosError> {
+        let mismatch = |field: &Field| TaosError {
+            code: TaosCode::TscInvalidValue,
+            err: format!("value {:?} mismatches column type {:?}", field, ty).into(),
+        };
+        macro_rules! values {
+            ($($variant:ident)|+ => $v:ident => $value:expr) => {
+                fields
+                    .into_iter()
+                    .map(|field| match field {
+                        Field::Null => Ok(None),
+                        $(Field::$variant($v))|+ => Ok(Some($value)),
+                        field => Err(mismatch(field)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+            };
+        }
+        Ok(match ty {
+            TaosDataType::Bool => values!(Bool => v => *v).into(),
+            TaosDataType::TinyInt => values!(TinyInt => v => *v).into(),
+            TaosDataType::SmallInt => values!(SmallInt => v => *v).into(),
+            TaosDataType::Int => values!(Int => v => *v).into(),
+            TaosDataType::BigInt => values!(BigInt => v => *v).into(),
+            TaosDataType::UTinyInt => values!(UTinyInt => v => *v).into(),
+            TaosDataType::USmallInt => values!(USmallInt => v => *v).into(),
+            TaosDataType::UInt => values!(UInt => v => *v).into(),
+            TaosDataType::UBigInt => values!(UBigInt => v => *v).into(),
+            TaosDataType::Float => values!(Float => v => *v).into(),
+            TaosDataType::Double => values!(Double => v => *v).into(),
+            TaosDataType::Timestamp => {
+                let values: Vec<Option<i64>> = fields
+                    .into_iter()
+                    .map(|field| match field {
+                        Field::Null => Ok(None),
+                        Field::Timestamp(v) => v
+                            .to_precision(precision)
+                            .map(|v| Some(v.as_raw_timestamp()))
+                            .ok_or_else(|| TaosError {
+                                code: TaosCode::TscInvalidValue,
+                                err: format!("timestamp {:?} out of {:?} precision", v, precision)
+                                    .into(),
+                            }),
+                        field => Err(mismatch(field)),
+                    })
+                    .collect::<Result<_, _>>()?;
+                ColumnView::from_fixed(ty, values)
+            }
+            TaosDataType::Binary | TaosDataType::NChar => {
+                let values: Vec<Option<&[u8]>> = fields
+                    .into_iter()
+                    .map(|field| match field {
+                        Field::Null => Ok(None),
+                        Field::Binary(v) => Ok(Some(&v[..])),
+                        Field::NChar(v) => Ok(Some(v.as_bytes())),
+                        field => Err(mismatch(field)),
+                    })
+                    .collect::<Result<_, _>>()?;
+                ColumnView::from_var(ty, values)
+            }
+            _ => {
+                return Err(TaosError {
+                    code: TaosCode::TscInvalidValue,
+                    err: format!("unsupported column type {:?}", ty).into(),
+                })
+            }
+        })
+    }
+
+    pub fn data_type(&self) -> TaosDataType {
+        self.ty
+    }
+
+    /// Number of rows.
+    pub fn len(&self) -> usize {
+        self.nulls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nulls.is_empty()
+    }
+
+    pub fn is_null(&self, i: usize) -> bool {
+        self.nulls[i] != 0
+    }
+
+    /// The multi-bind struct refers to the column buffers, it must not outlive the column.
+    pub(crate) fn as_multi_bind(&self) -> TAOS_MULTI_BIND {
+        TAOS_MULTI_BIND {
+            buffer_type: self.ty as _,
+            buffer: self.buffer.as_ptr() as *mut c_void,
+            buffer_length: self.buffer_length,
+            length: self.lengths.as_ptr() as *mut i32,
+            is_null: self.nulls.as_ptr() as *mut c_char,
+            num: self.len() as _,
+        }
+    }
+}
+
+macro_rules! _impl_fixed_column_view {
+    ($ty:ty, $target:ident) => {
+        impl From<Vec<Option<$ty>>> for ColumnView {
+            fn from(values: Vec<Option<$ty>>) -> Self {
+                ColumnView::from_fixed(TaosDataType::$target, values)
+            }
+        }
+        impl From<Vec<$ty>> for ColumnView {
+            fn from(values: Vec<$ty>) -> Self {
+                values.into_iter().map(Some).collect_vec().into()
+            }
+        }
+        impl From<&[$ty]> for ColumnView {
+            fn from(values: &[$ty]) -> Self {
+                values.iter().copied().map(Some).collect_vec().into()
+            }
+        }
+    };
+}
+
+_impl_fixed_column_view!(i8, TinyInt);
+_impl_fixed_column_view!(i16, SmallInt);
+_impl_fixed_column_view!(i32, Int);
+_impl_fixed_column_view!(i64, BigInt);
+_impl_fixed_column_view!(u8, UTinyInt);
+_impl_fixed_column_view!(u16, USmallInt);
+_impl_fixed_column_view!(u32, UInt);
+_impl_fixed_column_view!(u64, UBigInt);
+_impl_fixed_column_view!(f32, Float);
+_impl_fixed_column_view!(f64, Double);
+
+impl From<Vec<Option<bool>>> for ColumnView {
+    fn from(values: Vec<Option<bool>>) -> Self {
+        let values = values.into_iter().map(|v| v.map(|v| v as i8)).collect();
+        ColumnView::from_fixed(TaosDataType::Bool, values)
+    }
+}
+impl From<Vec<bool>> for ColumnView {
+    fn from(values: Vec<bool>) -> Self {
+        values.into_iter().map(Some).collect_vec().into()
+    }
+}
+
+impl From<Vec<Option<Timestamp>>> for ColumnView {
+    fn from(values: Vec<Option<Timestamp>>) -> Self {
+        let values = values
+            .into_iter()
+            .map(|v| v.map(|v| v.as_raw_timestamp()))
+            .collect();
+        ColumnView::from_fixed(TaosDataType::Timestamp, values)
+    }
+}
+impl From<Vec<Timestamp>> for ColumnView {
+    fn from(values: Vec<Timestamp>) -> Self {
+        values.into_iter().map(Some).collect_vec().into()
+    }
+}
+
+macro_rules! _impl_var_column_view {
+    ($ty:ty, $target:ident) => {
+        impl From<Vec<Option<$ty>>> for ColumnView {
+            fn from(values: Vec<Option<$ty>>) -> Self {
+                ColumnView::from_var(TaosDataType::$target, values)
+            }
+        }
+        impl From<Vec<$ty>> for ColumnView {
+            fn from(values: Vec<$ty>) -> Self {
+                values.into_iter().map(Some).collect_vec().into()
+            }
+        }
+    };
+}
+
+_impl_var_column_view!(String, NChar);
+_impl_var_column_view!(&str, NChar);
+_impl_var_column_view!(BString, Binary);
+_impl_var_column_view!(&BStr, Binary);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test column view of fixed length values
+    fn fixed_column_view() {
+        let view = ColumnView::from(vec![Some(1i64), None, Some(3)]);
+        assert_eq!(view.data_type(), TaosDataType::BigInt);
+        assert_eq!(view.len(), 3);
+        assert!(view.is_null(1));
+        let bind = view.as_multi_bind();
+        assert_eq!(bind.buffer_length, 8);
+        assert_eq!(bind.num, 3);
+        let values = unsafe { std::slice::from_raw_parts(bind.buffer as *const i64, 3) };
+        assert_eq!(values, &[1, 0, 3]);
+        assert_eq!(view.lengths, vec![8, 0, 8]);
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test column view of variable length values
+    fn var_column_view() {
+        let view = ColumnView::from(vec![Some("abc"), None, Some("涛思")]);
+        assert_eq!(view.data_type(), TaosDataType::NChar);
+        let bind = view.as_multi_bind();
+        assert_eq!(bind.buffer_length, "涛思".len());
+        assert_eq!(view.lengths, vec![3, 0, 6]);
+        let buffer = unsafe { std::slice::from_raw_parts(bind.buffer as *const u8, 18) };
+        assert_eq!(&buffer[0..3], b"abc");
+        assert_eq!(&buffer[12..18], "涛思".as_bytes());
+        assert_eq!(view.nulls, vec![0, 1, 0]);
+
+        let view = ColumnView::nulls(TaosDataType::Binary, 2);
+        assert!(view.is_null(0) && view.is_null(1));
+    }
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test column view from row values
+    fn fields_column_view() -> Result<(), TaosError> {
+        let milli = TimestampPrecision::Milli;
+        let view =
+            ColumnView::from_fields(TaosDataType::Int, milli, &[Field::Int(1), Field::Null])?;
+        assert_eq!(view.data_type(), TaosDataType::Int);
+        assert!(!view.is_null(0) && view.is_null(1));
+        assert_eq!(view.lengths, vec![4, 0]);
+
+        let view = ColumnView::from_fields(
+            TaosDataType::Binary,
+            milli,
+            &[Field::Binary("abc".into()), Field::NChar("涛思".into())],
+        )?;
+        assert_eq!(view.data_type(), TaosDataType::Binary);
+        assert_eq!(view.lengths, vec![3, 6]);
+
+        let ts = [
+            Field::Timestamp(Timestamp::new(1626006833639, milli)),
+            Field::Timestamp(Timestamp::new(1626006833639001, TimestampPrecision::Micro)),
+        ];
+        let view = ColumnView::from_fields(TaosDataType::Timestamp, milli, &ts)?;
+        let values =
+            unsafe { std::slice::from_raw_parts(view.as_multi_bind().buffer as *const i64, 2) };
+        assert_eq!(values, &[1626006833639, 1626006833639]);
+        let view = ColumnView::from_fields(TaosDataType::Timestamp, TimestampPrecision::Nano, &ts)?;
+        let values =
+            unsafe { std::slice::from_raw_parts(view.as_multi_bind().buffer as *const i64, 2) };
+        assert_eq!(values, &[1626006833639000000, 1626006833639001000]);
+        let ts = [Field::Timestamp(Timestamp::new(i64::MAX, milli))];
+        assert!(
+            ColumnView::from_fields(TaosDataType::Timestamp, TimestampPrecision::Nano, &ts)
+                .is_err()
+        );
+
+        let err =
+            ColumnView::from_fields(TaosDataType::Int, milli, &[Field::BigInt(1)]).unwrap_err();
+        assert_eq!(err.code, TaosCode::TscInvalidValue);
+        Ok(())
+    }
+}
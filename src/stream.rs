@@ -0,0 +1,275 @@
+//! Continuous query by `taos_open_stream`.
+use crate::bindings::*;
+use crate::de::RowDeserializer;
+use crate::*;
+
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::Stream;
+use serde::de::DeserializeOwned;
+
+use std::marker::PhantomData;
+use std::os::raw::c_void;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+type StreamRow = (Arc<Vec<ColumnMeta>>, Vec<Field>);
+
+/// State shared with the stream callbacks.
+struct StreamState {
+    sender: Mutex<Option<UnboundedSender<StreamRow>>>,
+    meta: Mutex<Option<(Arc<Vec<ColumnMeta>>, TimestampPrecision)>>,
+}
+
+unsafe extern "C" fn stream_callback(param: *mut c_void, res: *mut TAOS_RES, row: TAOS_ROW) {
+    let state = &*(param as *const StreamState);
+    if row.is_null() {
+        return;
+    }
+    let res = CTaosResult::borrowed(res);
+    let (meta, precision) = state
+        .meta
+        .lock()
+        .unwrap()
+        .get_or_insert_with(|| (Arc::new(res.column_meta()), res.precision()))
+        .clone();
+    let row = res.to_row(row, &meta, precision);
+    if let Some(sender) = state.sender.lock().unwrap().as_ref() {
+        let _ = sender.unbounded_send((meta, row));
+    }
+}
+
+unsafe extern "C" fn stop_callback(param: *mut c_void) {
+    let state = &*(param as *const StreamState);
+    state.sender.lock().unwrap().take();
+}
+
+/// Rows of a continuous query, the stream is closed on drop.
+///
+/// It ends when the continuous query is stopped by server.
+pub struct TaosStream<'a> {
+    stream: *mut TAOS_STREAM,
+    state: *mut StreamState,
+    receiver: UnboundedReceiver<StreamRow>,
+    _taos: PhantomData<&'a Taos>,
+}
+
+unsafe impl<'a> Send for TaosStream<'a> {}
+
+impl<'a> TaosStream<'a> {
+    /// Column metadata of rows, it's available after the first row is computed.
+    pub fn column_meta(&self) -> Option<Arc<Vec<ColumnMeta>>> {
+        let state = unsafe { &*self.state };
+        let meta = state.meta.lock().unwrap();
+        meta.as_ref().map(|(meta, _)| meta.clone())
+    }
+
+    /// Deserialize each row into `T`, see [crate::de] for details.
+    pub fn deserialize<T: DeserializeOwned>(self) -> TaosStreamAs<'a, T> {
+        TaosStreamAs {
+            stream: self,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Stream for TaosStream<'a> {
+    type Item = Vec<Field>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().receiver)
+            .poll_next(cx)
+            .map(|row| row.map(|(_, row)| row))
+    }
+}
+
+impl<'a> Drop for TaosStream<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            taos_close_stream(self.stream);
+            drop(Box::from_raw(self.state));
+        }
+    }
+}
+
+/// Stream of deserialized rows of a continuous query, see [TaosStream::deserialize].
+pub struct TaosStreamAs<'a, T> {
+    stream: TaosStream<'a>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: DeserializeOwned> Stream for TaosStreamAs<'a, T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().stream.receiver)
+            .poll_next(cx)
+            .map(|row| {
+                row.map(|(meta, row)| Ok(T::deserialize(RowDeserializer::new(&meta, &row))?))
+            })
+    }
+}
+
+fn invalid_stream(msg: String) -> Error {
+    TaosError {
+        code: TaosCode::TscInvalidValue,
+        err: msg.into(),
+    }
+    .into()
+}
+
+/// Append the `interval` clause to `sql`, the interval is at least 1 millisecond and `sql` must
+/// not have an `interval` clause already.
+fn with_interval(sql: &str, interval: Duration) -> Result<String, Error> {
+    if interval < Duration::from_millis(1) {
+        return Err(invalid_stream(format!(
+            "interval {:?} of stream is less than 1ms",
+            interval
+        )));
+    }
+    let sql = sql.trim_end().trim_end_matches(';').trim_end();
+    let lower = sql.to_ascii_lowercase();
+    let has_interval = lower
+        .match_indices("interval")
+        .any(|(i, _)| lower[i + "interval".len()..].trim_start().starts_with('('));
+    if has_interval {
+        return Err(invalid_stream(format!(
+            "stream sql already has an interval clause: {}",
+            sql
+        )));
+    }
+    Ok(format!("{} interval({}a)", sql, interval.as_millis()))
+}
+
+impl Taos {
+    /// Open a continuous query, rows are computed periodically from `start`, or now if `None`.
+    ///
+    /// The `interval` clause will be appended to `sql` if specified, which must be at least 1ms,
+    /// and `sql` with an `interval` clause already is rejected.
+    ///
+    /// ```ignore
+    /// use futures::StreamExt;
+    /// let mut stream = taos.open_stream("select count(*) from m1", None, Some(Duration::from_secs(10)))?;
+    /// while let Some(row) = stream.next().await {
+    ///     println!("{}", row.into_iter().join(","));
+    /// }
+    /// ```
+    pub fn open_stream(
+        &self,
+        sql: &str,
+        start: Option<Timestamp>,
+        interval: Option<Duration>,
+    ) -> Result<TaosStream<'_>, Error> {
+        let sql = match interval {
+            Some(interval) => with_interval(sql, interval)?,
+            None => sql.to_string(),
+        };
+        let sql = sql.to_c_string();
+        let (sender, receiver) = unbounded();
+        let state = Box::into_raw(Box::new(StreamState {
+            sender: Mutex::new(Some(sender)),
+            meta: Mutex::new(None),
+        }));
+        let stream = unsafe {
+            taos_open_stream(
+                self.as_raw(),
+                sql.as_ptr(),
+                Some(stream_callback),
+                // INT64_MIN means starting from now.
+                start.map(|ts| ts.timestamp_millis()).unwrap_or(i64::MIN),
+                state as _,
+                Some(stop_callback),
+            )
+        };
+        if stream.is_null() {
+            unsafe { drop(Box::from_raw(state)) };
+            let err = std::ptr::null_mut::<c_void>().taos_error_or().err();
+            return Err(err
+                .unwrap_or_else(|| TaosError {
+                    code: TaosCode::Unknown,
+                    err: "open stream failed".into(),
+                })
+                .into());
+        }
+        Ok(TaosStream {
+            stream,
+            state,
+            receiver,
+            _taos: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::taos;
+    use crate::*;
+    use futures::StreamExt;
+    use proc_test_catalog::test_catalogue;
+
+    use std::time::Duration;
+
+    #[test]
+    #[test_catalogue]
+    /// Test interval clause appended to stream sql
+    fn stream_interval() {
+        use super::with_interval;
+
+        let sql = "select count(*) from tb1";
+        assert_eq!(
+            with_interval(sql, Duration::from_secs(10)).unwrap(),
+            "select count(*) from tb1 interval(10000a)"
+        );
+        assert_eq!(
+            with_interval("select count(*) from tb1; ", Duration::from_millis(1)).unwrap(),
+            "select count(*) from tb1 interval(1a)"
+        );
+        assert!(with_interval(sql, Duration::from_micros(999)).is_err());
+        assert!(with_interval(sql, Duration::ZERO).is_err());
+        assert!(with_interval(
+            "select count(*) from tb1 INTERVAL (1s);",
+            Duration::from_secs(1)
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    #[test_catalogue]
+    /// Test continuous query
+    async fn open_stream() -> Result<(), Error> {
+        let taos = taos()?;
+        let db = "rs_open_stream";
+        taos.exec(format!("drop database if exists {}", db)).await?;
+        taos.exec(format!("create database if not exists {} keep 36500", db))
+            .await?;
+        taos.exec(format!("use {}", db)).await?;
+        taos.exec("create table tb1 (ts timestamp, v int)").await?;
+        taos.exec("insert into tb1 values(1626006833639, 1) (1626006834639, 2)")
+            .await?;
+
+        #[derive(Debug, serde::Deserialize)]
+        struct Count {
+            ts: Timestamp,
+            #[serde(rename = "count(*)")]
+            count: i64,
+        }
+
+        let stream = taos.open_stream(
+            "select count(*) from tb1",
+            Some(Timestamp::new(1626006833000, TimestampPrecision::Milli)),
+            Some(Duration::from_secs(1)),
+        )?;
+        let mut stream = stream.deserialize::<Count>();
+        let row = tokio::time::timeout(Duration::from_secs(30), stream.next())
+            .await
+            .expect("stream row timeout")
+            .expect("stream should not be closed")?;
+        assert!(row.count > 0);
+        assert!(row.ts.as_raw_timestamp() > 0);
+        drop(stream);
+
+        taos.exec(format!("drop database {}", db)).await?;
+        Ok(())
+    }
+}
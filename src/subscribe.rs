@@ -0,0 +1,312 @@
+//! Subscribe newly arrived data by `taos_subscribe`.
+use crate::bindings::*;
+use crate::*;
+
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::Stream;
+
+use std::marker::PhantomData;
+use std::os::raw::{c_int, c_void};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// Collect all rows of a result set owned by the subscription.
+fn consume_result(res: *mut TAOS_RES) -> Result<TaosQueryData, TaosError> {
+    let is_null = res.is_null();
+    let res = CTaosResult::borrowed(res);
+    let code = res.error_code();
+    if !code.success() {
+        return Err(TaosError {
+            code,
+            err: res.error_string().into(),
+        });
+    }
+    if is_null {
+        return Ok(TaosQueryData {
+            column_meta: Vec::new(),
+            rows: Vec::new(),
+        });
+    }
+    let column_meta = res.column_meta();
+    let precision = res.precision();
+    let mut rows = Vec::new();
+    while let Some(row) = res.fetch_row(&column_meta, precision)? {
+        rows.push(row);
+    }
+    Ok(TaosQueryData { column_meta, rows })
+}
+
+/// A subscription of a topic, consume newly arrived rows by polling with [Subscription::consume].
+///
+/// It's unsubscribed on drop, the progress is kept by default so that it could be resumed by
+/// subscribing the same topic without restart.
+#[derive(Debug)]
+pub struct Subscription<'a> {
+    sub: *mut TAOS_SUB,
+    topic: String,
+    keep_progress: bool,
+    _taos: PhantomData<&'a Taos>,
+}
+
+unsafe impl<'a> Send for Subscription<'a> {}
+
+impl<'a> Subscription<'a> {
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Set whether to keep the progress when dropped, default is `true`.
+    pub fn set_keep_progress(&mut self, keep_progress: bool) {
+        self.keep_progress = keep_progress;
+    }
+
+    /// Consume rows arrived since last consuming, it may block to wait the subscription interval.
+    pub fn consume(&mut self) -> Result<TaosQueryData, Error> {
+        let res = unsafe { taos_consume(self.sub) };
+        Ok(consume_result(res)?)
+    }
+
+    /// Unsubscribe the topic, keep the progress to resume later or not.
+    pub fn unsubscribe(mut self, keep_progress: bool) {
+        self.keep_progress = keep_progress;
+    }
+
+    fn close(&mut self) {
+        if !self.sub.is_null() {
+            unsafe { taos_unsubscribe(self.sub, self.keep_progress as _) };
+            self.sub = std::ptr::null_mut();
+        }
+    }
+}
+
+impl<'a> Drop for Subscription<'a> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+type SubscribeSender = UnboundedSender<Result<TaosQueryData, TaosError>>;
+
+unsafe extern "C" fn subscribe_callback(
+    _sub: *mut TAOS_SUB,
+    res: *mut TAOS_RES,
+    param: *mut c_void,
+    code: c_int,
+) {
+    let sender = &*(param as *const SubscribeSender);
+    let data = if code == 0 {
+        consume_result(res)
+    } else {
+        Err(TaosError {
+            code: (code & 0x0000ffff).into(),
+            err: CTaosResult::borrowed(res).error_string().into(),
+        })
+    };
+    let _ = sender.unbounded_send(data);
+}
+
+/// Subscription in callback mode, newly arrived rows are yielded as a [Stream] periodically.
+#[derive(Debug)]
+pub struct SubscriptionStream<'a> {
+    subscription: Subscription<'a>,
+    sender: *mut SubscribeSender,
+    receiver: UnboundedReceiver<Result<TaosQueryData, TaosError>>,
+}
+
+unsafe impl<'a> Send for SubscriptionStream<'a> {}
+
+impl<'a> SubscriptionStream<'a> {
+    pub fn topic(&self) -> &str {
+        self.subscription.topic()
+    }
+
+    /// Set whether to keep the progress when dropped, default is `true`.
+    pub fn set_keep_progress(&mut self, keep_progress: bool) {
+        self.subscription.set_keep_progress(keep_progress);
+    }
+
+    /// Unsubscribe the topic, keep the progress to resume later or not.
+    pub fn unsubscribe(mut self, keep_progress: bool) {
+        self.set_keep_progress(keep_progress);
+    }
+}
+
+impl<'a> Stream for SubscriptionStream<'a> {
+    type Item = Result<TaosQueryData, TaosError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().receiver).poll_next(cx)
+    }
+}
+
+impl<'a> Drop for SubscriptionStream<'a> {
+    fn drop(&mut self) {
+        // callbacks should be stopped before the sender freed.
+        self.subscription.close();
+        unsafe { drop(Box::from_raw(self.sender)) };
+    }
+}
+
+impl Taos {
+    fn subscribe_raw(
+        &self,
+        topic: &str,
+        sql: &str,
+        restart: bool,
+        callback: TAOS_SUBSCRIBE_CALLBACK,
+        param: *mut c_void,
+        interval: Duration,
+    ) -> Result<Subscription<'_>, Error> {
+        let (topic_c, sql) = (topic.to_c_string(), sql.to_c_string());
+        let sub = unsafe {
+            taos_subscribe(
+                self.as_raw(),
+                restart as _,
+                topic_c.as_ptr(),
+                sql.as_ptr(),
+                callback,
+                param,
+                interval.as_millis() as _,
+            )
+        };
+        if sub.is_null() {
+            let err = std::ptr::null_mut::<c_void>().taos_error_or().err();
+            return Err(err
+                .unwrap_or_else(|| TaosError {
+                    code: TaosCode::Unknown,
+                    err: "subscribe failed".into(),
+                })
+                .into());
+        }
+        Ok(Subscription {
+            sub,
+            topic: topic.to_string(),
+            keep_progress: true,
+            _taos: PhantomData,
+        })
+    }
+
+    /// Subscribe `sql` as `topic` in polling mode, `restart` to ignore the progress saved before.
+    ///
+    /// ```ignore
+    /// let mut sub = taos.subscribe("topic", "select * from m1", false)?;
+    /// loop {
+    ///     let data = sub.consume()?;
+    ///     for row in data.rows {
+    ///         println!("{}", row.into_iter().join(","));
+    ///     }
+    ///     std::thread::sleep(Duration::from_secs(1));
+    /// }
+    /// ```
+    pub fn subscribe(
+        &self,
+        topic: &str,
+        sql: &str,
+        restart: bool,
+    ) -> Result<Subscription<'_>, Error> {
+        self.subscribe_raw(
+            topic,
+            sql,
+            restart,
+            None,
+            std::ptr::null_mut(),
+            Duration::ZERO,
+        )
+    }
+
+    /// Subscribe `sql` as `topic` in callback mode, new rows are consumed every `interval`.
+    ///
+    /// ```ignore
+    /// use futures::TryStreamExt;
+    /// let mut sub = taos.subscribe_stream("topic", "select * from m1", false, Duration::from_secs(1))?;
+    /// while let Some(data) = sub.try_next().await? {
+    ///     println!("{} rows arrived", data.rows.len());
+    /// }
+    /// ```
+    pub fn subscribe_stream(
+        &self,
+        topic: &str,
+        sql: &str,
+        restart: bool,
+        interval: Duration,
+    ) -> Result<SubscriptionStream<'_>, Error> {
+        let (sender, receiver) = unbounded();
+        let sender: *mut SubscribeSender = Box::into_raw(Box::new(sender));
+        match self.subscribe_raw(
+            topic,
+            sql,
+            restart,
+            Some(subscribe_callback),
+            sender as _,
+            interval,
+        ) {
+            Ok(subscription) => Ok(SubscriptionStream {
+                subscription,
+                sender,
+                receiver,
+            }),
+            Err(err) => {
+                unsafe { drop(Box::from_raw(sender)) };
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::taos;
+    use crate::*;
+    use futures::StreamExt;
+    use proc_test_catalog::test_catalogue;
+
+    use std::time::Duration;
+
+    #[tokio::test]
+    #[test_catalogue]
+    /// Test subscribe in polling and callback mode
+    async fn subscribe() -> Result<(), Error> {
+        let taos = taos()?;
+        let db = "rs_subscribe";
+        taos.exec(format!("drop database if exists {}", db)).await?;
+        taos.exec(format!("create database if not exists {} keep 36500", db))
+            .await?;
+        taos.exec(format!("use {}", db)).await?;
+        taos.exec("create table tb1 (ts timestamp, v int)").await?;
+        taos.exec("insert into tb1 values(1626006833639, 1)")
+            .await?;
+
+        let mut sub = taos.subscribe("rs_topic", "select * from tb1", true)?;
+        assert_eq!(sub.topic(), "rs_topic");
+        let data = sub.consume()?;
+        assert_eq!(data.rows.len(), 1);
+        taos.exec("insert into tb1 values(1626006833640, 2)")
+            .await?;
+        let data = sub.consume()?;
+        assert_eq!(data.rows.len(), 1);
+        assert_eq!(data.rows[0][1], Field::Int(2));
+        sub.unsubscribe(true);
+
+        // resume from the progress
+        let mut sub = taos.subscribe("rs_topic", "select * from tb1", false)?;
+        assert!(sub.consume()?.rows.is_empty());
+        sub.unsubscribe(false);
+
+        let mut stream = taos.subscribe_stream(
+            "rs_topic_stream",
+            "select * from tb1",
+            true,
+            Duration::from_millis(100),
+        )?;
+        let data = tokio::time::timeout(Duration::from_secs(10), stream.next())
+            .await
+            .expect("subscription timeout")
+            .expect("subscription should not be closed")?;
+        assert_eq!(data.rows.len(), 2);
+        stream.unsubscribe(false);
+
+        taos.exec(format!("drop database {}", db)).await?;
+        Ok(())
+    }
+}
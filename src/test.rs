@@ -4,7 +4,7 @@ pub fn var_or_default(env: &str, default: &str) -> String {
     std::env::var(env).unwrap_or(default.to_string())
 }
 
-pub fn taos() -> Result<Taos, Error> {
+pub fn cfg() -> TaosCfg {
     TaosCfgBuilder::default()
         .ip(&var_or_default("TEST_TAOS_IP", "127.0.0.1"))
         .user(&var_or_default("TEST_TAOS_USER", "root"))
@@ -17,5 +17,8 @@ pub fn taos() -> Result<Taos, Error> {
         )
         .build()
         .expect("ToasCfg builder error")
-        .connect()
+}
+
+pub fn taos() -> Result<Taos, Error> {
+    cfg().connect()
 }
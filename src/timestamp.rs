@@ -1,5 +1,6 @@
 use chrono::NaiveDateTime;
 use num_enum::FromPrimitive;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use std::{
@@ -41,6 +42,32 @@ impl Timestamp {
     pub fn as_raw_timestamp(&self) -> i64 {
         self.timestamp
     }
+    /// Timestamp in milliseconds, whatever the precision is.
+    pub fn timestamp_millis(&self) -> i64 {
+        match self.precision {
+            TimestampPrecision::Nano => self.timestamp.div_euclid(1_000_000),
+            TimestampPrecision::Micro => self.timestamp.div_euclid(1_000),
+            _ => self.timestamp,
+        }
+    }
+    /// Convert to `precision`, truncated if it's coarser.
+    ///
+    /// Returns `None` if either precision is unknown or the value overflows.
+    pub fn to_precision(&self, precision: TimestampPrecision) -> Option<Timestamp> {
+        let scale = |precision| match precision {
+            TimestampPrecision::Milli => Some(0u32),
+            TimestampPrecision::Micro => Some(1),
+            TimestampPrecision::Nano => Some(2),
+            TimestampPrecision::Unknown => None,
+        };
+        let (from, to) = (scale(self.precision)?, scale(precision)?);
+        let timestamp = if from > to {
+            self.timestamp.div_euclid(1000i64.pow(from - to))
+        } else {
+            self.timestamp.checked_mul(1000i64.pow(to - from))?
+        };
+        Some(Timestamp::new(timestamp, precision))
+    }
     pub fn to_std_time(&self) -> SystemTime {
         let duration = match self.precision {
             TimestampPrecision::Nano => time::Duration::from_nanos(self.timestamp.abs() as _),
@@ -99,3 +126,48 @@ impl Display for Timestamp {
         write!(f, "{}", self.to_naive_datetime().format(format))
     }
 }
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TimestampVisitor;
+
+        impl<'de> Visitor<'de> for TimestampVisitor {
+            type Value = Timestamp;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a timestamp in milliseconds or a datetime string")
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(Timestamp::new(v, TimestampPrecision::Milli))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(Timestamp::new(v as _, TimestampPrecision::Milli))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Timestamp::from_str(v).map_err(E::custom)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let timestamp: i64 = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let precision: i64 = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                Ok(Timestamp::new(timestamp, precision as i32))
+            }
+
+            fn visit_newtype_struct<D: Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<Self::Value, D::Error> {
+                deserializer.deserialize_any(self)
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(crate::de::TIMESTAMP_NEWTYPE, TimestampVisitor)
+    }
+}
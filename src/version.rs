@@ -0,0 +1,169 @@
+//! Versions of client library and server.
+use crate::*;
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// TDengine version in format of `major.minor.patch.build`, like `2.4.0.7`.
+///
+/// Suffix after `-`, like `-beta`, is ignored in parsing and comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub build: u32,
+}
+
+impl Version {
+    pub const fn new(major: u32, minor: u32, patch: u32, build: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            build,
+        }
+    }
+
+    /// Versions with the same major version are compatible.
+    pub fn is_compatible(&self, other: &Version) -> bool {
+        self.major == other.major
+    }
+}
+
+impl FromStr for Version {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidVersion(s.to_string());
+        let version = s.trim().split('-').next().unwrap_or_default();
+        let parts: Vec<u32> = version
+            .split('.')
+            .map(|part| part.parse().map_err(|_| invalid()))
+            .collect::<Result<_, _>>()?;
+        if !(2..=4).contains(&parts.len()) {
+            return Err(invalid());
+        }
+        let part = |i: usize| parts.get(i).copied().unwrap_or(0);
+        Ok(Version::new(part(0), part(1), part(2), part(3)))
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.patch, self.build
+        )
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch, self.build).cmp(&(
+            other.major,
+            other.minor,
+            other.patch,
+            other.build,
+        ))
+    }
+}
+
+/// Return error if major versions of client and server differ.
+pub(crate) fn check_compatible(client: Version, server: Version) -> Result<(), Error> {
+    if client.is_compatible(&server) {
+        Ok(())
+    } else {
+        Err(Error::IncompatibleVersion { client, server })
+    }
+}
+
+#[cfg(feature = "native")]
+mod native {
+    use super::*;
+    use crate::bindings::*;
+
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+
+    pub(super) fn parse_info(info: *const c_char) -> Result<Version, Error> {
+        if info.is_null() {
+            return Err(Error::InvalidVersion(String::new()));
+        }
+        unsafe { CStr::from_ptr(info) }.to_string_lossy().parse()
+    }
+
+    /// Version of the native client library.
+    pub fn client_version() -> Result<Version, Error> {
+        parse_info(unsafe { taos_get_client_info() })
+    }
+
+    impl Taos {
+        /// Version of the connected server, which is cached by the client library at connect.
+        pub fn server_version(&self) -> Result<Version, Error> {
+            parse_info(unsafe { taos_get_server_info(self.as_raw()) })
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+pub use native::client_version;
+
+#[cfg(feature = "rest")]
+impl crate::rest::Taos {
+    /// Version of the server, by `select server_version()`.
+    pub async fn server_version(&self) -> Result<Version, Error> {
+        let data = self.query("select server_version()").await?;
+        match data.rows.first().and_then(|row| row.first()) {
+            Some(Field::Binary(v)) => v.to_string().parse(),
+            Some(Field::NChar(v)) => v.parse(),
+            v => Err(Error::InvalidVersion(format!("{:?}", v))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test version parsing and compatibility
+    fn parse_version() -> Result<(), Error> {
+        let v: Version = "2.4.0.7".parse()?;
+        assert_eq!(v, Version::new(2, 4, 0, 7));
+        assert_eq!(v.to_string(), "2.4.0.7");
+        assert_eq!("2.0".parse::<Version>()?, Version::new(2, 0, 0, 0));
+        assert_eq!("3.0.0.0-beta".parse::<Version>()?, Version::new(3, 0, 0, 0));
+        assert!("2".parse::<Version>().is_err());
+        assert!("2.a.0".parse::<Version>().is_err());
+        assert!("1.2.3.4.5".parse::<Version>().is_err());
+
+        assert!(Version::new(2, 4, 0, 7) > Version::new(2, 3, 9, 9));
+        assert!(check_compatible(Version::new(2, 4, 0, 7), Version::new(2, 0, 20, 0)).is_ok());
+        assert!(matches!(
+            check_compatible(Version::new(2, 4, 0, 7), Version::new(3, 0, 0, 0)),
+            Err(Error::IncompatibleVersion { .. })
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    #[proc_test_catalog::test_catalogue]
+    /// Test client and server version of native connection
+    fn native_version() -> Result<(), Error> {
+        let taos = crate::test::taos()?;
+        let client = client_version()?;
+        let server = taos.server_version()?;
+        assert!(client.is_compatible(&server));
+        Ok(())
+    }
+}
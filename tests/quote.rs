@@ -21,5 +21,15 @@ async fn double_quote() -> Result<(), Error> {
     let _ = taos.exec("insert into tb1 values(now, 1.0)").await;
     let res = taos.query("select * from stb1").await?;
     dbg!(&res);
+
+    let tag = "abc\"def'\\ghi";
+    taos.exec(&sql!(
+        "create table if not exists {} using stb1 tags({})",
+        sql::ident("tb-2")?,
+        tag
+    )?)
+    .await?;
+    let res = taos.query("select tag1 from `tb-2`").await?;
+    assert_eq!(res.rows[0][0].to_string(), tag);
     Ok(())
 }